@@ -0,0 +1,489 @@
+use bevy::prelude::Entity;
+use bevy::utils::{ HashMap, HashSet };
+use criterion::measurement::{ Measurement, ValueFormatter };
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    BatchSize,
+    BenchmarkId,
+    Criterion,
+    Throughput,
+};
+use redstone_rust::*;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::Harness;
+
+/// A square of dust with a redstone block in the middle; ports are left unconnected.
+fn dust_mat(size: i64) -> Chunks {
+    let mut chunks = Chunks::new();
+    for x in 0..size {
+        for y in 0..size {
+            chunks.set_block(x, y, Some(REDSTONE_DUST));
+        }
+    }
+    chunks.set_block(size / 2, size / 2, Some(REDSTONE_BLOCK));
+    chunks
+}
+
+fn update_dust_ports_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_dust_ports");
+    for size in [16, 32, 64] {
+        let mat = dust_mat(size);
+        group.throughput(Throughput::Elements((size * size) as u64));
+        group.bench_with_input(BenchmarkId::new("dense_mat", size), &size, |b, size| {
+            b.iter_batched(
+                || mat.clone(),
+                |mut chunks| {
+                    let mut listeners = EventListeners::new();
+                    let mut propagation_queue = PropagationQueue::new();
+                    let mut calculations = 0;
+                    for x in 0..*size {
+                        for y in 0..*size {
+                            update_dust_ports(
+                                &mut chunks,
+                                x,
+                                y,
+                                &mut listeners,
+                                &mut propagation_queue,
+                                &mut calculations
+                            );
+                        }
+                    }
+                    chunks
+                },
+                BatchSize::LargeInput
+            )
+        });
+    }
+    group.finish();
+}
+
+const SLIME_ROWS: [i64; 3] = [-1, 0, 1];
+const SLIME_COLUMNS: [i64; 4] = [1, 2, 3, 4];
+const PUSH_LIMIT: usize = 12;
+
+/// The first `count` cells of a 3x4 wall of slime right of (0, 0).
+fn slime_wall(count: usize) -> Harness {
+    let mut harness = Harness::new();
+    let cells = SLIME_ROWS.iter().flat_map(|x| SLIME_COLUMNS.iter().map(move |y| (*x, *y)));
+    for (x, y) in cells.take(count) {
+        harness.place(SLIME, Orientation::Up, x, y);
+    }
+    harness
+}
+
+fn piston_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("slime_push");
+    group.throughput(Throughput::Elements(PUSH_LIMIT as u64));
+
+    let mut harness = slime_wall(PUSH_LIMIT);
+    group.bench_function("get_power", |b| {
+        b.iter(|| get_power(&harness.chunks, 0, 1, Orientation::Right, PUSH_LIMIT as u32))
+    });
+
+    group.bench_function("move_blocks", |b| {
+        b.iter(|| {
+            let Harness { chunks, listeners, propagation_queue, texture_to_block_map } = &mut harness;
+            let mut calculations = 0;
+            for (x, y, orientation) in [
+                (0, 1, Orientation::Right),
+                (0, 2, Orientation::Left),
+            ] {
+                let affected_blocks = get_power(chunks, x, y, orientation, PUSH_LIMIT as u32);
+                move_blocks(
+                    chunks,
+                    x,
+                    y,
+                    orientation,
+                    &affected_blocks,
+                    listeners,
+                    propagation_queue,
+                    &mut calculations,
+                    &mut HashSet::new(),
+                    orientation.get_opposing(),
+                    (x, y)
+                );
+                // land the wall straight away so the next push can move it back
+                let mut cells: Vec<(i64, i64)> = listeners.moving_blocks.keys().copied().collect();
+                cells.sort();
+                for (cell_x, cell_y) in cells {
+                    finish_moving(
+                        chunks,
+                        cell_x,
+                        cell_y,
+                        listeners,
+                        propagation_queue,
+                        &mut calculations,
+                        texture_to_block_map
+                    );
+                }
+            }
+        })
+    });
+
+    let mut harness = slime_wall(PUSH_LIMIT);
+    harness.place(STICKY_PISTON, Orientation::Right, 0, 0);
+    group.bench_function("sticky_piston", |b| {
+        b.iter(|| {
+            for signal in [16, 0] {
+                harness.set_signal(0, 0, signal);
+                let mut calculations = 0;
+                execute_mechanism(
+                    &mut harness.chunks,
+                    0,
+                    0,
+                    MechanismEvent::InputChange(signal > 0),
+                    &mut harness.listeners,
+                    &mut harness.propagation_queue,
+                    &mut calculations,
+                    &harness.texture_to_block_map
+                );
+            }
+        })
+    });
+    group.finish();
+}
+
+/// `count` torch clocks: each torch feeds a dust loop back into its own input and toggles
+/// every tick.
+fn torch_clocks(count: i64, spacing: i64) -> Harness {
+    let mut harness = Harness::new();
+    for clock in 0..count {
+        let y = clock * spacing;
+        harness.place(REDSTONE_DUST, Orientation::Up, 0, y + 1);
+        harness.place(REDSTONE_DUST, Orientation::Up, 1, y + 1);
+        harness.place(REDSTONE_DUST, Orientation::Up, 1, y);
+        harness.place(REDSTONE_TORCH, Orientation::Up, 0, y);
+    }
+    harness
+}
+
+const CLOCK_TICKS: u64 = 1000;
+
+fn clock_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clock");
+    group.sample_size(10);
+    for count in [1, 16, 64] {
+        let mut harness = torch_clocks(count, 3);
+        group.throughput(Throughput::Elements(CLOCK_TICKS));
+        group.bench_with_input(BenchmarkId::new("torch_clocks", count), &count, |b, _| {
+            b.iter(|| harness.tick(CLOCK_TICKS))
+        });
+    }
+    group.finish();
+}
+
+/// Clocks this far apart are separate regions
+const REGION_SPACING: i64 = 4;
+
+fn regions_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("regions");
+    group.sample_size(10);
+    for count in [16, 64, 256] {
+        group.throughput(Throughput::Elements(CLOCK_TICKS));
+        let mut sequential = torch_clocks(count, REGION_SPACING);
+        group.bench_with_input(BenchmarkId::new("sequential", count), &count, |b, _| {
+            b.iter(|| sequential.tick(CLOCK_TICKS))
+        });
+
+        let mut parallel = torch_clocks(count, REGION_SPACING);
+        group.bench_with_input(BenchmarkId::new("parallel", count), &count, |b, _| {
+            b.iter(|| parallel.tick_regions(CLOCK_TICKS))
+        });
+    }
+    group.finish();
+}
+
+/// A two piston flying machine heading `direction`, its rear observer at (x, y), set off by a
+/// block placed behind the observer. It keeps moving a cell at a time until something stops
+/// its front.
+fn flying_machine(harness: &mut Harness, x: i64, y: i64, direction: Orientation) {
+    let step = if direction == Orientation::Right { 1 } else { -1 };
+    let back = direction.get_opposing();
+    let parts = [
+        (0, 1, PISTON, direction),
+        (1, 0, SLIME, direction),
+        (1, 1, SLIME, direction),
+        (0, 2, SLIME, direction),
+        (0, 3, SLIME, direction),
+        (1, 2, STICKY_PISTON, back),
+        (0, 0, OBSERVER, direction),
+        (1, 3, OBSERVER, back),
+    ];
+    for (dx, dy, blk, orientation) in parts {
+        harness.place(blk, orientation, x + dx, y + step * dy);
+    }
+    harness.tick(6);
+    harness.place(DIRT, Orientation::Up, x, y - step);
+}
+
+const FLIGHT_TICKS: u64 = 400;
+
+fn flying_machine_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flying_machine");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(FLIGHT_TICKS));
+    group.bench_function("both_ways", |b| {
+        b.iter_batched(
+            || {
+                // both cross into the next chunk on their way
+                let mut harness = Harness::new();
+                flying_machine(&mut harness, 0, 10, Orientation::Right);
+                flying_machine(&mut harness, 4, 5, Orientation::Left);
+                harness
+            },
+            |mut harness| {
+                harness.tick(FLIGHT_TICKS);
+                harness
+            },
+            BatchSize::LargeInput
+        )
+    });
+    group.finish();
+}
+
+const WORLD_SIZE: usize = 100_000;
+
+/// A world of `WORLD_SIZE` blocks mixing plain blocks, dust and mechanisms.
+fn large_world() -> Chunks {
+    let palette = [DIRT, REDSTONE_DUST, REPEATER, COMPARATOR, REDSTONE_TORCH, SLIME, PISTON];
+    let width = (WORLD_SIZE as f64).sqrt().ceil() as usize;
+    let mut chunks = Chunks::new();
+    for idx in 0..WORLD_SIZE {
+        let (x, y) = ((idx / width) as i64, (idx % width) as i64);
+        chunks.set_block(x, y, Some(palette[idx % palette.len()]));
+    }
+    chunks
+}
+
+fn autosave_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("autosave");
+    group.sample_size(10);
+
+    let dir = std::env::temp_dir().join(format!("redstone_rust_bench_{}", std::process::id()));
+    let mut storage = ChunkStorage::open(&dir);
+    let listeners = EventListeners::new();
+    let chunks = large_world();
+
+    group.throughput(Throughput::Elements(WORLD_SIZE as u64));
+    group.bench_function("whole_world", |b| {
+        b.iter_batched(
+            || chunks.clone(),
+            |mut chunks| {
+                storage.save_changed(&mut chunks, &listeners);
+                chunks
+            },
+            BatchSize::LargeInput
+        )
+    });
+
+    // what a tick of a running circuit leaves to save
+    let mut saved = chunks.clone();
+    storage.save_changed(&mut saved, &listeners);
+    let obsidian = block_registry().get("Obsidian").unwrap();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("one_changed_chunk", |b| {
+        b.iter_batched(
+            || {
+                let mut chunks = saved.clone();
+                chunks.set_block(0, 0, Some(obsidian));
+                chunks
+            },
+            |mut chunks| {
+                storage.save_changed(&mut chunks, &listeners);
+                chunks
+            },
+            BatchSize::LargeInput
+        )
+    });
+    group.finish();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A chunk as the engine first stored it: a whole `Option<Block>` per cell, next to the sprite
+/// entity drawing it, with the chunks keyed by i128 coordinates.
+#[derive(Clone)]
+struct BaselineChunk {
+    map: [[Option<Block>; CHUNK_SIZE.0 as usize]; CHUNK_SIZE.1 as usize],
+    #[allow(dead_code)]
+    entity_map: [[Option<Entity>; CHUNK_SIZE.1 as usize]; CHUNK_SIZE.0 as usize],
+}
+
+struct BaselineChunks(HashMap<(i128, i128), BaselineChunk>);
+
+impl BaselineChunks {
+    fn from_world_coord(x: i128, y: i128) -> ((i128, i128), (usize, usize)) {
+        let (width, height) = (CHUNK_SIZE.0 as i128, CHUNK_SIZE.1 as i128);
+        let u = x.rem_euclid(width) as usize;
+        let v = y.rem_euclid(height) as usize;
+        ((x.div_euclid(width), y.div_euclid(height)), (u, v))
+    }
+
+    fn get_block(&self, x: i128, y: i128) -> Option<Block> {
+        let (chunk_coord, (u, v)) = Self::from_world_coord(x, y);
+        self.0.get(&chunk_coord).and_then(|chunk| chunk.map[u][v])
+    }
+
+    fn set_block(&mut self, x: i128, y: i128, blk: Option<Block>) {
+        let (chunk_coord, (u, v)) = Self::from_world_coord(x, y);
+        let chunk = self.0.entry(chunk_coord).or_insert_with(|| BaselineChunk {
+            map: [[None; CHUNK_SIZE.0 as usize]; CHUNK_SIZE.1 as usize],
+            entity_map: [[None; CHUNK_SIZE.1 as usize]; CHUNK_SIZE.0 as usize],
+        });
+        chunk.map[u][v] = blk;
+    }
+
+    fn copy_of(chunks: &Chunks) -> BaselineChunks {
+        let mut baseline = BaselineChunks(HashMap::new());
+        for ((x, y), blk) in chunks.blocks() {
+            baseline.set_block(x as i128, y as i128, Some(blk));
+        }
+        baseline
+    }
+}
+
+/// Measures bytes instead of time, so criterion reports the memory a chunk takes up. The
+/// routine returns the bytes of every chunk it looked at, criterion divides by the count.
+struct ChunkMemory;
+
+impl Measurement for ChunkMemory {
+    type Intermediate = ();
+    type Value = u64;
+
+    fn start(&self) {}
+
+    fn end(&self, _: ()) -> u64 {
+        0
+    }
+
+    fn add(&self, v1: &u64, v2: &u64) -> u64 {
+        v1 + v2
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+impl ValueFormatter for ChunkMemory {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "B"
+    }
+
+    fn scale_throughputs(&self, _typical_value: f64, _throughput: &Throughput, _values: &mut [f64]) -> &'static str {
+        "B"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "B"
+    }
+}
+
+/// Sums `size` over `count` chunks, going round the world's chunks in turn.
+fn chunk_bytes<C>(chunks: &[C], count: u64, size: impl Fn(&C) -> usize) -> u64 {
+    chunks
+        .iter()
+        .cycle()
+        .take(count as usize)
+        .map(|chunk| black_box(size(chunk)) as u64)
+        .sum()
+}
+
+/// The worlds the storage layouts are compared on. The mixed world is over half
+/// components, far more than a built circuit.
+fn storage_worlds() -> [(&'static str, Chunks); 2] {
+    [("dust", dust_mat(64)), ("mixed", large_world())]
+}
+
+fn chunk_memory_benchmark(c: &mut Criterion<ChunkMemory>) {
+    let mut group = c.benchmark_group("chunk_memory");
+    for (name, chunks) in storage_worlds() {
+        let baseline = BaselineChunks::copy_of(&chunks);
+        let baseline: Vec<&BaselineChunk> = baseline.0.values().collect();
+        group.bench_function(BenchmarkId::new("baseline", name), |b| {
+            b.iter_custom(|count| chunk_bytes(&baseline, count, |_| std::mem::size_of::<BaselineChunk>()))
+        });
+        let loaded: Vec<&Chunk> = chunks.loaded.values().collect();
+        group.bench_function(BenchmarkId::new("palette", name), |b| {
+            b.iter_custom(|count| chunk_bytes(&loaded, count, |chunk| chunk.memory_size()))
+        });
+    }
+    group.finish();
+}
+
+fn chunk_storage_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_storage");
+    for (name, chunks) in storage_worlds() {
+        let mut cells: Vec<((i64, i64), Block)> = chunks.blocks().collect();
+        cells.sort_by_key(|(coord, _)| *coord);
+        let mut baseline = BaselineChunks::copy_of(&chunks);
+        let mut chunks = chunks;
+        group.throughput(Throughput::Elements(cells.len() as u64));
+
+        group.bench_function(BenchmarkId::new("get_block/baseline", name), |b| {
+            b.iter(|| {
+                cells
+                    .iter()
+                    .filter(|((x, y), _)| baseline.get_block(*x as i128, *y as i128).is_some())
+                    .count()
+            })
+        });
+        group.bench_function(BenchmarkId::new("get_block/palette", name), |b| {
+            b.iter(|| cells.iter().filter(|((x, y), _)| chunks.get_block(*x, *y).is_some()).count())
+        });
+
+        // writes back each block with its signal changed, as a tick does
+        group.bench_function(BenchmarkId::new("set_block/baseline", name), |b| {
+            b.iter(|| {
+                for ((x, y), blk) in &cells {
+                    let mut blk = *blk;
+                    if let Some(redstone) = &mut blk.redstone {
+                        redstone.signal ^= 1;
+                    }
+                    baseline.set_block(*x as i128, *y as i128, Some(blk));
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("set_block/palette", name), |b| {
+            b.iter(|| {
+                for ((x, y), blk) in &cells {
+                    let mut blk = *blk;
+                    if let Some(redstone) = &mut blk.redstone {
+                        redstone.signal ^= 1;
+                    }
+                    chunks.set_block(*x, *y, Some(blk));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    update_dust_ports_benchmark,
+    piston_benchmark,
+    clock_benchmark,
+    regions_benchmark,
+    flying_machine_benchmark,
+    autosave_benchmark,
+    chunk_storage_benchmark
+);
+criterion_group! {
+    name = memory;
+    config = Criterion::default().with_measurement(ChunkMemory).without_plots();
+    targets = chunk_memory_benchmark
+}
+criterion_main!(benches, memory);
@@ -0,0 +1,434 @@
+use criterion::{ criterion_group, criterion_main, BenchmarkId, Criterion };
+use redstone_rust::*;
+
+/// The recursive solver `propagate_signal_at` used before the work-queue rewrite, kept here
+/// as the baseline. Overflowing jobs and repropagation are drained in `settle` the same way
+/// `delayed_redstone_listeners` used to drain them across frames.
+mod recursive {
+    use redstone_rust::*;
+    use std::collections::HashSet;
+
+    /// A propagation call put off until the stack unwinds: position, input port, input and
+    /// previous signal, and the signal type it came with
+    type Job = (i64, i64, Option<Orientation>, u8, u8, Option<SignalType>);
+
+    #[derive(Default)]
+    pub struct State {
+        overflow: Vec<Job>,
+        repropagate: HashSet<(i64, i64)>,
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle(
+        chunks: &mut Chunks,
+        x: i64,
+        y: i64,
+        from_port: Option<Orientation>,
+        input_signal: u8,
+        previous_signal: u8,
+        prev_signal_type: Option<SignalType>,
+        listeners: &mut EventListeners
+    ) {
+        let mut state = State::default();
+        let mut calculations = 0;
+        propagate_signal_at(
+            chunks,
+            x,
+            y,
+            from_port,
+            input_signal,
+            previous_signal,
+            prev_signal_type,
+            listeners,
+            &mut state,
+            &mut calculations
+        );
+
+        while !state.overflow.is_empty() || !state.repropagate.is_empty() {
+            while !state.overflow.is_empty() {
+                let queue = std::mem::take(&mut state.overflow);
+                calculations = 0;
+                for (x, y, from_port, input_signal, previous_signal, prev_signal_type) in queue {
+                    propagate_signal_at(
+                        chunks,
+                        x,
+                        y,
+                        from_port,
+                        input_signal,
+                        previous_signal,
+                        prev_signal_type,
+                        listeners,
+                        &mut state,
+                        &mut calculations
+                    );
+                }
+            }
+
+            let queue = std::mem::take(&mut state.repropagate);
+            for (x, y) in queue {
+                let (from_port, previous_signal, prev_signal_type) = get_max_prev(chunks, x, y);
+                let transmitted_signal = if previous_signal > 0 { previous_signal - 1 } else { 0 };
+                propagate_signal_at(
+                    chunks,
+                    x,
+                    y,
+                    from_port,
+                    transmitted_signal,
+                    previous_signal,
+                    prev_signal_type,
+                    listeners,
+                    &mut state,
+                    &mut calculations
+                );
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn propagate_signal_at(
+        chunks: &mut Chunks,
+        x: i64,
+        y: i64,
+        from_port: Option<Orientation>,
+        input_signal: u8,
+        previous_signal: u8,
+        prev_signal_type: Option<SignalType>,
+        listeners: &mut EventListeners,
+        state: &mut State,
+        calculations: &mut u32
+    ) {
+        if input_signal == 0 && previous_signal <= 1 {
+            return;
+        }
+
+        let mut curr_blk = chunks.get_block(x, y);
+        let blk_ref = curr_blk;
+
+        let (
+            signal,
+            signal_type,
+            kind,
+            input_ports,
+            output_ports,
+            signal_type_port_mapping,
+            is_redstone_component,
+        ) = match curr_blk {
+            Some(
+                Block {
+                    redstone: Some(
+                        Redstone {
+                            ref mut signal,
+                            ref mut signal_type,
+                            kind,
+                            input_ports,
+                            output_ports,
+                            signal_type_port_mapping,
+                            is_redstone_component,
+                        },
+                    ),
+                    ..
+                },
+            ) =>
+                (
+                    signal,
+                    signal_type,
+                    kind,
+                    input_ports,
+                    output_ports,
+                    signal_type_port_mapping,
+                    is_redstone_component,
+                ),
+            _ => {
+                return;
+            }
+        };
+
+        if let Some(from_port) = from_port {
+            if !input_ports[from_port.to_port_idx()] {
+                return;
+            }
+
+            match kind {
+                Some(RedstoneKind::Dust) | Some(RedstoneKind::Block) => {
+                    if let Some(SignalType::Weak(false)) | None = prev_signal_type {
+                        return;
+                    }
+                }
+                Some(RedstoneKind::Mechanism) => {
+                    if input_signal > 0 {
+                        listeners.turn_mechanism_on(x, y, &blk_ref.unwrap());
+                    } else {
+                        listeners.turn_mechanism_off(x, y, &blk_ref.unwrap());
+                    }
+                }
+                None => {
+                    if let Some(SignalType::Weak(false)) | Some(SignalType::Strong(false)) = prev_signal_type {
+                        return;
+                    }
+                }
+            }
+        }
+
+        *calculations += 1;
+        if *calculations > 10000 {
+            state.overflow.push((x, y, from_port, input_signal, previous_signal, prev_signal_type));
+            return;
+        }
+
+        if
+            input_signal >= *signal ||
+            (previous_signal == *signal + 1 && *signal > 0 && input_signal == 0)
+        {
+            if from_port.is_some() && input_signal == *signal && input_signal > 0 {
+                return;
+            }
+
+            let output_signal_type = match *signal_type {
+                Some(curr_signal_type) => {
+                    if let SignalType::Strong(true) = curr_signal_type {
+                        if from_port.is_some() && is_redstone_component {
+                            return;
+                        }
+                    } else if let SignalType::Weak(true) = curr_signal_type {
+                        if let Some(SignalType::Weak(false)) | None = prev_signal_type {
+                            return;
+                        }
+                    }
+
+                    if
+                        input_signal == 0 &&
+                        ((curr_signal_type == SignalType::Weak(false) &&
+                            prev_signal_type == Some(SignalType::Weak(true))) ||
+                            (curr_signal_type == SignalType::Strong(false) &&
+                                prev_signal_type == Some(SignalType::Strong(true))))
+                    {
+                        *signal_type = None;
+                    }
+                    curr_signal_type
+                }
+                None => {
+                    match prev_signal_type {
+                        Some(SignalType::Strong(true)) => {
+                            if input_signal > 0 {
+                                *signal_type = Some(SignalType::Strong(false));
+                            }
+                            SignalType::Strong(false)
+                        }
+                        Some(SignalType::Weak(true)) => {
+                            if input_signal > 0 {
+                                *signal_type = Some(SignalType::Weak(false));
+                            }
+                            SignalType::Weak(false)
+                        }
+                        _ => {
+                            return;
+                        }
+                    }
+                }
+            };
+
+            let current_signal = *signal;
+            *signal = input_signal;
+            chunks.set_block(x, y, curr_blk);
+
+            if input_signal == 1 {
+                return;
+            }
+
+            let transmitted_signal = if input_signal > 0 { input_signal - 1 } else { 0 };
+            listeners.entity_map_update.insert((x, y));
+            for (idx, port) in output_ports.iter().enumerate() {
+                if *port {
+                    let port_orientation = Orientation::port_idx_to_orientation(idx);
+                    let (next_x, next_y) = port_orientation.get_next_coord(x, y);
+                    let port_output_signal_type = signal_type_port_mapping[idx].unwrap_or(
+                        output_signal_type
+                    );
+
+                    propagate_signal_at(
+                        chunks,
+                        next_x,
+                        next_y,
+                        Some(port_orientation.get_opposing()),
+                        transmitted_signal,
+                        current_signal,
+                        Some(port_output_signal_type),
+                        listeners,
+                        state,
+                        calculations
+                    );
+                }
+            }
+        }
+        if input_signal == 0 && prev_signal_type.is_some() {
+            state.repropagate.insert((x, y));
+        }
+    }
+}
+
+const LEVER_SPACING: i64 = 16;
+
+/// Dust cells with a lever every `LEVER_SPACING` cells, so circuits longer than the
+/// 15-block signal range still have every cell powered.
+struct Circuit {
+    chunks: Chunks,
+    levers: Vec<(i64, i64)>,
+}
+
+impl Circuit {
+    fn new(cells: impl Iterator<Item = (i64, i64)>, is_lever: impl Fn(i64, i64) -> bool) -> Circuit {
+        let mut chunks = Chunks::new();
+        let mut listeners = EventListeners::new();
+        let mut propagation_queue = PropagationQueue::new();
+        let mut calculations = 0;
+        let mut levers = Vec::new();
+        let mut dust = Vec::new();
+
+        for (x, y) in cells {
+            if is_lever(x, y) {
+                chunks.set_block(x, y, Some(LEVER));
+                levers.push((x, y));
+            } else {
+                chunks.set_block(x, y, Some(REDSTONE_DUST));
+                dust.push((x, y));
+            }
+        }
+        for (x, y) in dust {
+            update_dust_ports(
+                &mut chunks,
+                x,
+                y,
+                &mut listeners,
+                &mut propagation_queue,
+                &mut calculations
+            );
+        }
+        Circuit { chunks, levers }
+    }
+
+    fn dust_line(length: i64) -> Circuit {
+        Circuit::new(
+            (0..length).map(|y| (0, y)),
+            |_, y| y % LEVER_SPACING == 0
+        )
+    }
+
+    fn dust_grid(size: i64) -> Circuit {
+        Circuit::new(
+            (0..size).flat_map(move |x| (0..size).map(move |y| (x, y))),
+            |x, y| x % LEVER_SPACING == 0 && y % LEVER_SPACING == 0
+        )
+    }
+
+    fn duplicate(&self) -> Circuit {
+        Circuit { chunks: self.chunks.clone(), levers: self.levers.clone() }
+    }
+}
+
+fn signals(chunks: &Chunks) -> Vec<((i64, i64), u8)> {
+    let mut signals: Vec<((i64, i64), u8)> = Vec::new();
+    for (coord, blk) in chunks.blocks() {
+        if let Block { redstone: Some(Redstone { signal, .. }), .. } = blk {
+            signals.push((coord, signal));
+        }
+    }
+    signals.sort();
+    signals
+}
+
+fn switch_iterative(circuit: &mut Circuit, on: bool, propagation_queue: &mut PropagationQueue) {
+    let mut listeners = EventListeners::new();
+    let mut calculations = 0;
+    let (input_signal, previous_signal) = if on { (16, 16) } else { (0, 17) };
+    for (x, y) in circuit.levers.iter() {
+        propagate_signal_at(
+            &mut circuit.chunks,
+            *x,
+            *y,
+            None,
+            input_signal,
+            previous_signal,
+            None,
+            &mut listeners,
+            propagation_queue,
+            &mut calculations
+        );
+    }
+}
+
+fn switch_recursive(circuit: &mut Circuit, on: bool) {
+    let mut listeners = EventListeners::new();
+    let (input_signal, previous_signal) = if on { (16, 16) } else { (0, 17) };
+    for (x, y) in circuit.levers.iter() {
+        recursive::settle(
+            &mut circuit.chunks,
+            *x,
+            *y,
+            None,
+            input_signal,
+            previous_signal,
+            None,
+            &mut listeners
+        );
+    }
+}
+
+/// Both solvers must settle on the same strengths before their timings are comparable.
+fn assert_same_steady_state(circuit: &Circuit) {
+    let mut iterative = circuit.duplicate();
+    let mut recursive = circuit.duplicate();
+    let mut propagation_queue = PropagationQueue::new();
+
+    for on in [true, false] {
+        switch_iterative(&mut iterative, on, &mut propagation_queue);
+        switch_recursive(&mut recursive, on);
+        assert_eq!(signals(&iterative.chunks), signals(&recursive.chunks));
+    }
+}
+
+fn bench_circuits(c: &mut Criterion, name: &str, circuits: Vec<(i64, Circuit)>) {
+    let mut group = c.benchmark_group(name);
+    for (size, circuit) in circuits {
+        assert_same_steady_state(&circuit);
+
+        let mut iterative = circuit.duplicate();
+        let mut propagation_queue = PropagationQueue::new();
+        group.bench_with_input(BenchmarkId::new("iterative", size), &size, |b, _| {
+            b.iter(|| {
+                switch_iterative(&mut iterative, true, &mut propagation_queue);
+                switch_iterative(&mut iterative, false, &mut propagation_queue);
+            })
+        });
+
+        let mut recursive = circuit.duplicate();
+        group.bench_with_input(BenchmarkId::new("recursive", size), &size, |b, _| {
+            b.iter(|| {
+                switch_recursive(&mut recursive, true);
+                switch_recursive(&mut recursive, false);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn propagation_benchmark(c: &mut Criterion) {
+    bench_circuits(
+        c,
+        "dust_line",
+        [64, 256, 1024, 4096]
+            .into_iter()
+            .map(|length| (length, Circuit::dust_line(length)))
+            .collect()
+    );
+    bench_circuits(
+        c,
+        "dust_grid",
+        [16, 32, 64]
+            .into_iter()
+            .map(|size| (size, Circuit::dust_grid(size)))
+            .collect()
+    );
+}
+
+criterion_group!(benches, propagation_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,932 @@
+pub use super::*;
+
+fn get_signal(blk: &Block) -> u8 {
+    if let Some(Redstone { signal, .. }) = blk.redstone { signal } else { 0 }
+}
+
+fn lit_state(blk: &Block) -> usize {
+    if get_signal(blk) > 0 { 1 } else { 0 }
+}
+
+/// Whether the block on `side` of (x, y) drives power into it.
+fn is_powered_from(chunks: &Chunks, x: i64, y: i64, side: Orientation) -> bool {
+    let (next_x, next_y) = side.get_next_coord(x, y);
+    match chunks.get_block(next_x, next_y) {
+        Some(source @ Block { redstone: Some(rs), .. }) => {
+            rs.output_ports[side.get_opposing().to_port_idx()] && output_strength(&source) > 0
+        }
+        _ => false,
+    }
+}
+
+/// Whether any neighbour drives power into (x, y).
+fn is_powered(chunks: &Chunks, x: i64, y: i64) -> bool {
+    Orientation::iter()
+        .into_iter()
+        .any(|side| is_powered_from(chunks, x, y, side))
+}
+
+/// Tells comparators reading (x, y) through a conductive block that its level changed. The
+/// caller alerts the direct neighbours.
+fn alert_comparators_behind(chunks: &Chunks, listeners: &mut EventListeners, x: i64, y: i64) {
+    for orientation in Orientation::iter() {
+        let (next_x, next_y) = orientation.get_next_coord(x, y);
+        if chunks.get_block(next_x, next_y).is_some_and(|next| next.is_conductive()) {
+            let (behind_x, behind_y) = orientation.get_next_coord(next_x, next_y);
+            listeners.change_state(behind_x, behind_y, orientation.get_opposing(), chunks);
+        }
+    }
+}
+
+/// Whether the block in front of the component is a repeater or comparator that is not
+/// pointing back at it. Vanilla gives such chains priority so they update front to back.
+fn is_facing_diode(chunks: &Chunks, x: i64, y: i64, orientation: Orientation) -> bool {
+    let (next_x, next_y) = orientation.get_next_coord(x, y);
+    match chunks.get_block(next_x, next_y) {
+        Some(Block {
+            mechanism: Some(MechanismKind::Repeater { .. } | MechanismKind::Comparator { .. }),
+            orientation: next_orientation,
+            ..
+        }) => next_orientation != orientation.get_opposing(),
+        _ => false,
+    }
+}
+
+/// A torch turning off this many times within `TORCH_BURNOUT_WINDOW` game ticks burns out
+const TORCH_BURNOUT_TOGGLES: usize = 8;
+const TORCH_BURNOUT_WINDOW: u64 = 60;
+
+/// Game ticks before a burnt out torch tries to relight
+const TORCH_RECOVERY_DELAY: u64 = 160;
+
+pub struct TorchMechanism;
+
+impl TorchMechanism {
+    fn set_burnt_out(chunks: &mut Chunks, x: i64, y: i64, burnt_out: bool) {
+        if let Some(blk) = chunks.get_block(x, y) {
+            let mechanism = if burnt_out { MechanismKind::BurntOutTorch } else { MechanismKind::RedstoneTorch };
+            chunks.set_block(x, y, Some(Block { mechanism: Some(mechanism), ..blk }));
+            chunks.mark_dirty(x, y);
+        }
+    }
+}
+
+impl Mechanism for TorchMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Signal
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        if (get_signal(&blk) > 0) == powered {
+            let delay = GAME_TICKS_PER_REDSTONE_TICK - INPUT_LATENCY;
+            ctx.listeners.schedule_tick(x, y, powered, delay, TickPriority::Normal);
+        }
+    }
+
+    /// Like vanilla, turning off counts towards burnout. A burnt out torch stays off while it
+    /// keeps being updated too often and relights on the first tick after that, at the latest
+    /// `TORCH_RECOVERY_DELAY` game ticks after burning out.
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        let signal = get_signal(&blk);
+        let burnout = ctx.chunks.settings.torch_burnout;
+        if powered && signal > 0 {
+            ctx.depower(x, y);
+            if
+                burnout &&
+                ctx.listeners.scheduled_ticks.record_toggle(x, y, TORCH_BURNOUT_WINDOW) >=
+                    TORCH_BURNOUT_TOGGLES
+            {
+                Self::set_burnt_out(ctx.chunks, x, y, true);
+                ctx.listeners.schedule_tick(x, y, powered, TORCH_RECOVERY_DELAY, TickPriority::Normal);
+            }
+        } else if !powered && signal == 0 {
+            if blk.mechanism == Some(MechanismKind::BurntOutTorch) {
+                if
+                    burnout &&
+                    ctx.listeners.scheduled_ticks.recent_toggles(x, y, TORCH_BURNOUT_WINDOW) >=
+                        TORCH_BURNOUT_TOGGLES
+                {
+                    return;
+                }
+                Self::set_burnt_out(ctx.chunks, x, y, false);
+            }
+            ctx.power(x, y);
+        }
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        if blk.mechanism == Some(MechanismKind::BurntOutTorch) { 2 } else { lit_state(blk) }
+    }
+}
+
+pub struct PistonMechanism;
+
+impl PistonMechanism {
+    /// Lands the blocks the piston at (x, y) is still moving.
+    fn land_moving_blocks(ctx: &mut MechanismContext, x: i64, y: i64) {
+        let mut cells: Vec<(i64, i64)> = ctx.listeners.moving_blocks
+            .iter()
+            .filter(|(_, moving)| moving.piston == (x, y))
+            .map(|(cell, _)| *cell)
+            .collect();
+        cells.sort();
+        for (cell_x, cell_y) in cells.iter() {
+            finish_moving(
+                ctx.chunks,
+                *cell_x,
+                *cell_y,
+                ctx.listeners,
+                ctx.propagation_queue,
+                ctx.calculations,
+                ctx.texture_to_block_map
+            );
+        }
+    }
+
+    /// Quasi-connectivity, the 2D stand-in for Java pistons also reading power one block above
+    /// themselves: a source outputting into any cell next to the piston other than its front,
+    /// so diagonally or two blocks away, powers it too.
+    fn is_quasi_powered(chunks: &Chunks, x: i64, y: i64, orientation: Orientation) -> bool {
+        Orientation::iter()
+            .into_iter()
+            .filter(|side| *side != orientation)
+            .any(|side| {
+                let (side_x, side_y) = side.get_next_coord(x, y);
+                Orientation::iter()
+                    .into_iter()
+                    .filter(|dir| *dir != side.get_opposing())
+                    .any(|dir| is_powered_from(chunks, side_x, side_y, dir))
+            })
+    }
+}
+
+impl Mechanism for PistonMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Movement
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        let (extended, is_sticky) = if
+            let Block { mechanism: Some(MechanismKind::Piston { extended, sticky }), .. } = blk
+        {
+            (extended, sticky)
+        } else {
+            return;
+        };
+        let signal = if let Some(rs) = blk.redstone {
+            rs.signal
+        } else {
+            return;
+        };
+        let orientation = blk.orientation;
+        let powered =
+            signal > 0 ||
+            (ctx.chunks.settings.quasi_connectivity &&
+                Self::is_quasi_powered(ctx.chunks, x, y, orientation));
+        let piston_head = if is_sticky { STICKY_PISTON_HEAD } else { PISTON_HEAD };
+        let push_limit = ctx.chunks.settings.push_limit;
+        let mut traversed = HashSet::new();
+        set_movable(ctx.chunks, x, y, false);
+        if powered && !extended {
+            Self::land_moving_blocks(ctx, x, y);
+            let (next_x, next_y) = orientation.get_next_coord(x, y);
+            let affected_blocks = get_power(ctx.chunks, next_x, next_y, orientation, push_limit);
+            let moved = move_blocks(
+                ctx.chunks,
+                next_x,
+                next_y,
+                orientation,
+                &affected_blocks,
+                ctx.listeners,
+                ctx.propagation_queue,
+                ctx.calculations,
+                &mut traversed,
+                orientation.get_opposing(),
+                (x, y)
+            );
+            if moved {
+                set_extended(ctx.chunks, x, y, true);
+
+                place_moving(
+                    ctx.chunks,
+                    ctx.listeners,
+                    Block { orientation, ..piston_head },
+                    (x, y),
+                    next_x,
+                    next_y,
+                    (x, y),
+                    DetachedTicks::default()
+                );
+                ctx.listeners.update_entity(x, y);
+            } else {
+                // Blocked: it stays retracted until its input or a neighbour changes again
+                set_movable(ctx.chunks, x, y, true);
+            }
+        } else if extended && !powered {
+            // Unpowered before the extension finished: the blocks land where they were pushed
+            // to and a sticky piston leaves the block it pushed behind
+            let (next_x, next_y) = orientation.get_next_coord(x, y);
+            let (next_next_x, next_next_y) = orientation.get_next_coord(next_x, next_y);
+            let dropped = ctx.listeners.moving_blocks
+                .get(&(next_next_x, next_next_y))
+                .is_some_and(|moving| moving.piston == (x, y));
+            Self::land_moving_blocks(ctx, x, y);
+            set_extended(ctx.chunks, x, y, false);
+
+            ctx.listeners.update_entity(x, y);
+
+            if let Some(next_blk) = ctx.chunks.get_block(next_x, next_y) {
+                if next_blk.texture_name == piston_head.texture_name {
+                    destroy(
+                        ctx.chunks,
+                        next_x,
+                        next_y,
+                        ctx.listeners,
+                        ctx.propagation_queue,
+                        ctx.calculations
+                    );
+                }
+            }
+            if is_sticky && !dropped {
+                let pull_dir = orientation.get_opposing();
+                let affected_blocks = get_power(ctx.chunks, next_next_x, next_next_y, pull_dir, push_limit);
+                move_blocks(
+                    ctx.chunks,
+                    next_next_x,
+                    next_next_y,
+                    pull_dir,
+                    &affected_blocks,
+                    ctx.listeners,
+                    ctx.propagation_queue,
+                    ctx.calculations,
+                    &mut traversed,
+                    pull_dir,
+                    (x, y)
+                );
+            }
+            set_movable(ctx.chunks, x, y, true);
+        } else {
+            set_movable(ctx.chunks, x, y, !extended);
+        }
+    }
+
+    /// A neighbour changing makes the piston check its power again. That is how a blocked piston
+    /// retries, and the only way power reaching it by quasi-connectivity is noticed, so a piston
+    /// can be left extended or retracted until then (budding).
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _sides: [bool; 4]) {
+        self.on_input_change(ctx, x, y, blk, false);
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        if let Some(MechanismKind::Piston { extended: true, .. }) = blk.mechanism { 1 } else { 0 }
+    }
+}
+
+pub struct RepeaterMechanism;
+
+impl RepeaterMechanism {
+    fn delay(blk: &Block) -> u64 {
+        let tick = if let Some(MechanismKind::Repeater { tick, .. }) = blk.mechanism { tick } else { 0 };
+        ((tick as u64) + 1) * GAME_TICKS_PER_REDSTONE_TICK
+    }
+
+    /// Whether a powered repeater or comparator points into one of the sides of the repeater
+    fn is_locked(chunks: &Chunks, x: i64, y: i64, orientation: Orientation) -> bool {
+        Orientation::iter()
+            .into_iter()
+            .filter(|side| *side != orientation && *side != orientation.get_opposing())
+            .any(|side| {
+                let (side_x, side_y) = side.get_next_coord(x, y);
+                match chunks.get_block(side_x, side_y) {
+                    Some(
+                        side_blk @ Block {
+                            mechanism: Some(MechanismKind::Repeater { .. }),
+                            ..
+                        },
+                    ) => side_blk.orientation == side.get_opposing() && get_signal(&side_blk) > 0,
+                    // Comparators hold their output plus one
+                    Some(
+                        side_blk @ Block {
+                            mechanism: Some(MechanismKind::Comparator { .. }),
+                            ..
+                        },
+                    ) => side_blk.orientation == side.get_opposing() && get_signal(&side_blk) > 1,
+                    _ => false,
+                }
+            })
+    }
+
+    /// Records the repeater's input and whether it is locked, returning the updated block.
+    fn refresh(chunks: &mut Chunks, x: i64, y: i64, blk: Block, input: bool) -> Block {
+        let tick = if let Some(MechanismKind::Repeater { tick, .. }) = blk.mechanism {
+            tick
+        } else {
+            return blk;
+        };
+        let locked = Self::is_locked(chunks, x, y, blk.orientation);
+        let mechanism = Some(MechanismKind::Repeater { tick, locked, input });
+        if mechanism == blk.mechanism {
+            return blk;
+        }
+
+        let updated = Block { mechanism, ..blk };
+        chunks.set_block(x, y, Some(updated));
+        chunks.mark_dirty(x, y);
+        updated
+    }
+}
+
+impl Mechanism for RepeaterMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Signal
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        let blk = Self::refresh(ctx.chunks, x, y, blk, powered);
+        if let Some(MechanismKind::Repeater { locked: true, .. }) = blk.mechanism {
+            return;
+        }
+
+        let output = get_signal(&blk) > 0;
+        if output == powered || ctx.listeners.scheduled_ticks.is_scheduled(x, y) {
+            return;
+        }
+
+        let priority = if is_facing_diode(ctx.chunks, x, y, blk.orientation) {
+            TickPriority::ExtremelyHigh
+        } else if output {
+            TickPriority::VeryHigh
+        } else {
+            TickPriority::High
+        };
+        ctx.listeners.schedule_tick(x, y, powered, Self::delay(&blk) - INPUT_LATENCY, priority);
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        if let Some(MechanismKind::Repeater { locked: true, .. }) = blk.mechanism {
+            return;
+        }
+
+        if get_signal(&blk) > 0 {
+            if !powered {
+                ctx.depower(x, y);
+            }
+        } else {
+            ctx.power(x, y);
+            // A pulse shorter than the delay still comes out as long as the delay
+            if !powered {
+                ctx.listeners.schedule_tick(x, y, false, Self::delay(&blk), TickPriority::VeryHigh);
+            }
+        }
+    }
+
+    /// Locking and unlocking come from the sides, the rear input is unchanged
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _sides: [bool; 4]) {
+        if let Some(MechanismKind::Repeater { input, .. }) = blk.mechanism {
+            self.on_input_change(ctx, x, y, blk, input);
+        }
+    }
+
+    /// Unlocked sprites fill the first row, locked ones the second
+    fn render_state(&self, blk: &Block) -> usize {
+        let (tick, locked) = if let Some(MechanismKind::Repeater { tick, locked, .. }) = blk.mechanism {
+            (tick, locked)
+        } else {
+            (0, false)
+        };
+        let row_ind = if locked { 1 } else { 0 };
+        row_ind * 8 + (tick as usize) * 2 + lit_state(blk)
+    }
+
+    fn on_interact(&self, chunks: &mut Chunks, _listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        if let Some(MechanismKind::Repeater { tick, locked, input }) = blk.mechanism {
+            let mechanism = Some(MechanismKind::Repeater { tick: (tick + 1) % 4, locked, input });
+            chunks.set_block(x, y, Some(Block { mechanism, ..blk }));
+        }
+    }
+}
+
+pub struct ObserverMechanism;
+
+impl Mechanism for ObserverMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::StateChange
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, _blk: Block, powered: bool) {
+        if powered {
+            let delay = GAME_TICKS_PER_REDSTONE_TICK - INPUT_LATENCY;
+            ctx.listeners.schedule_tick(x, y, true, delay, TickPriority::Normal);
+        }
+    }
+
+    /// Changes seen while a tick is pending are ignored, as in vanilla. They reach the observer
+    /// a game tick after they happen, so it stays scheduled for one more game tick once its
+    /// pulse ends to ignore those that happened while it was still pulsing.
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, sides: [bool; 4]) {
+        if ctx.listeners.scheduled_ticks.is_scheduled(x, y) {
+            return;
+        }
+        if sides[blk.orientation.get_opposing().to_port_idx()] {
+            self.on_input_change(ctx, x, y, blk, true);
+        }
+    }
+
+    /// Fires two game ticks after the change it saw, which schedules it with `powered` set, and
+    /// stays on for two more.
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        if powered {
+            ctx.power(x, y);
+            ctx.listeners.schedule_tick(x, y, false, GAME_TICKS_PER_REDSTONE_TICK, TickPriority::Normal);
+        } else if get_signal(&blk) > 0 {
+            ctx.depower(x, y);
+            ctx.listeners.schedule_tick(x, y, false, INPUT_LATENCY, TickPriority::Normal);
+        }
+    }
+}
+
+pub struct LeverMechanism;
+
+impl Mechanism for LeverMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Interaction
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        if get_signal(&blk) > 0 {
+            ctx.depower(x, y);
+        } else {
+            ctx.power(x, y);
+        }
+    }
+
+    fn on_interact(&self, _chunks: &mut Chunks, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        listeners.turn_mechanism_on(x, y, &blk);
+    }
+}
+
+/// Redstone ticks a stone button stays pressed
+pub const STONE_BUTTON_TICKS: u64 = 10;
+/// Redstone ticks a wooden button stays pressed
+pub const WOODEN_BUTTON_TICKS: u64 = 15;
+
+pub struct ButtonMechanism;
+
+impl ButtonMechanism {
+    pub fn press_ticks(blk: &Block) -> u64 {
+        match blk.mechanism {
+            Some(MechanismKind::WoodenButton) => WOODEN_BUTTON_TICKS,
+            _ => STONE_BUTTON_TICKS,
+        }
+    }
+}
+
+impl Mechanism for ButtonMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Interaction
+    }
+
+    /// Pressing a button that is already pressed does nothing, so it always releases
+    /// `press_ticks` redstone ticks after the first press.
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        if get_signal(&blk) == 0 {
+            ctx.power(x, y);
+            let delay = Self::press_ticks(&blk) * GAME_TICKS_PER_REDSTONE_TICK;
+            ctx.listeners.schedule_tick(x, y, false, delay, TickPriority::Normal);
+        }
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, _blk: Block, _powered: bool) {
+        ctx.depower(x, y);
+    }
+
+    fn inspect(&self, blk: &Block) -> Option<String> {
+        Some(format!("pressed for {} redstone ticks", Self::press_ticks(blk)))
+    }
+
+    fn on_interact(&self, _chunks: &mut Chunks, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        listeners.turn_mechanism_on(x, y, &blk);
+    }
+}
+
+pub struct ComparatorMechanism;
+
+impl ComparatorMechanism {
+    /// Strength the block on the `port` side drives into the comparator at (x, y). Powered solid
+    /// blocks only reach the rear, sides read dust and components.
+    fn get_port_signal(chunks: &Chunks, x: i64, y: i64, port: Orientation, is_side: bool) -> u8 {
+        let (next_x, next_y) = port.get_next_coord(x, y);
+        let blk = if let Some(blk) = chunks.get_block(next_x, next_y) {
+            blk
+        } else {
+            return 0;
+        };
+        let rs = if let Some(rs) = blk.redstone {
+            rs
+        } else {
+            return 0;
+        };
+        if !rs.output_ports[port.get_opposing().to_port_idx()] || (is_side && rs.kind.is_none()) {
+            return 0;
+        }
+        output_strength(&blk)
+    }
+
+    /// Containers behind the comparator are measured instead of the signal, also through one
+    /// solid block while the signal is below full strength.
+    fn get_rear_signal(chunks: &Chunks, x: i64, y: i64, rear_port: Orientation) -> u8 {
+        let signal = Self::get_port_signal(chunks, x, y, rear_port, false);
+        let (rear_x, rear_y) = rear_port.get_next_coord(x, y);
+        let rear = if let Some(rear) = chunks.get_block(rear_x, rear_y) {
+            rear
+        } else {
+            return signal;
+        };
+        if let Some(level) = analog_output(&rear) {
+            return level;
+        }
+
+        if rear.is_conductive() && signal < 15 {
+            let (behind_x, behind_y) = rear_port.get_next_coord(rear_x, rear_y);
+            if let Some(level) = chunks.get_block(behind_x, behind_y).and_then(|behind| analog_output(&behind)) {
+                return level;
+            }
+        }
+        signal
+    }
+
+    /// The signal the comparator should hold given its current inputs
+    fn get_output(chunks: &Chunks, x: i64, y: i64, blk: &Block) -> Option<u8> {
+        let mode = if let Some(MechanismKind::Comparator { mode }) = blk.mechanism {
+            mode
+        } else {
+            return None;
+        };
+        let rear_port = blk.orientation.get_opposing();
+        let rear_signal = Self::get_rear_signal(chunks, x, y, rear_port);
+        let side_signal = Orientation::iter()
+            .into_iter()
+            .filter(|side| *side != blk.orientation && *side != rear_port)
+            .map(|side| Self::get_port_signal(chunks, x, y, side, true))
+            .max()
+            .unwrap_or(0);
+
+        let signal = match mode {
+            ComparatorModes::Compare => {
+                if rear_signal >= side_signal { rear_signal } else { 0 }
+            }
+            ComparatorModes::Subtract => rear_signal.saturating_sub(side_signal),
+        };
+
+        // The held signal is one above the output strength, like other components
+        Some(std::cmp::min(signal + 1, 16))
+    }
+}
+
+impl Mechanism for ComparatorMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Signal
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        if ctx.listeners.scheduled_ticks.is_scheduled(x, y) {
+            return;
+        }
+        if Self::get_output(ctx.chunks, x, y, &blk) == Some(get_signal(&blk)) {
+            return;
+        }
+
+        let priority = if is_facing_diode(ctx.chunks, x, y, blk.orientation) {
+            TickPriority::High
+        } else {
+            TickPriority::Normal
+        };
+        let delay = GAME_TICKS_PER_REDSTONE_TICK - INPUT_LATENCY;
+        ctx.listeners.schedule_tick(x, y, powered, delay, priority);
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        if let Some(output) = Self::get_output(ctx.chunks, x, y, &blk) {
+            let previous = get_signal(&blk) + 1;
+            ctx.emit(x, y, 0, previous);
+            ctx.emit(x, y, output, previous);
+        }
+    }
+
+    /// Containers and dust next to the comparator change without sending it a signal
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _sides: [bool; 4]) {
+        self.on_input_change(ctx, x, y, blk, false);
+    }
+
+    fn output_strength(&self, blk: &Block) -> u8 {
+        get_signal(blk).saturating_sub(1).min(15)
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        let row_ind = if let Some(MechanismKind::Comparator { mode: ComparatorModes::Subtract }) = blk.mechanism {
+            1
+        } else {
+            0
+        };
+        row_ind * 2 + lit_state(blk)
+    }
+
+    fn on_interact(&self, chunks: &mut Chunks, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        if let Some(MechanismKind::Comparator { mode }) = blk.mechanism {
+            let mode = if mode == ComparatorModes::Compare {
+                ComparatorModes::Subtract
+            } else {
+                ComparatorModes::Compare
+            };
+            let blk = Block { mechanism: Some(MechanismKind::Comparator { mode }), ..blk };
+            chunks.set_block(x, y, Some(blk));
+            listeners.turn_mechanism_on(x, y, &blk);
+        }
+    }
+}
+
+/// Items a container holds when full: 27 slots of 64.
+pub const CONTAINER_CAPACITY: u16 = 27 * 64;
+
+/// Items added by each right click.
+const CONTAINER_STACK: u16 = 64;
+
+pub struct ContainerMechanism;
+
+impl Mechanism for ContainerMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Interaction
+    }
+
+    fn on_input_change(&self, _ctx: &mut MechanismContext, _x: i64, _y: i64, _blk: Block, _powered: bool) {}
+
+    /// Vanilla's fill level: 0 when empty, then 1 to 15 with how full the container is
+    fn analog_output(&self, blk: &Block) -> Option<u8> {
+        let items = if let Some(MechanismKind::Container { items }) = blk.mechanism { items } else { 0 };
+        if items == 0 {
+            return Some(0);
+        }
+        let level = 1 + ((items.min(CONTAINER_CAPACITY) as u32) * 14) / (CONTAINER_CAPACITY as u32);
+        Some(level as u8)
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        self.analog_output(blk).unwrap_or(0) as usize
+    }
+
+    /// Adds a stack, emptying the container once it is full. Comparators reading through a
+    /// solid block are told as well, the caller alerts the direct neighbours.
+    fn on_interact(&self, chunks: &mut Chunks, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        let items = if let Some(MechanismKind::Container { items }) = blk.mechanism {
+            items
+        } else {
+            return;
+        };
+        let items = if items >= CONTAINER_CAPACITY { 0 } else { (items + CONTAINER_STACK).min(CONTAINER_CAPACITY) };
+        chunks.set_block(x, y, Some(Block { mechanism: Some(MechanismKind::Container { items }), ..blk }));
+        alert_comparators_behind(chunks, listeners, x, y);
+    }
+}
+
+/// Placeholder in a cell a piston is moving a block into
+pub struct MovingBlockMechanism;
+
+impl Mechanism for MovingBlockMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Movement
+    }
+
+    fn on_input_change(&self, _ctx: &mut MechanismContext, _x: i64, _y: i64, _blk: Block, _powered: bool) {}
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, _blk: Block, _powered: bool) {
+        finish_moving(
+            ctx.chunks,
+            x,
+            y,
+            ctx.listeners,
+            ctx.propagation_queue,
+            ctx.calculations,
+            ctx.texture_to_block_map
+        );
+    }
+}
+
+/// Game ticks a redstone lamp stays lit after losing power, 2 redstone ticks as in vanilla
+const LAMP_OFF_DELAY: u64 = 2 * GAME_TICKS_PER_REDSTONE_TICK;
+
+pub struct RedstoneLampMechanism;
+
+impl RedstoneLampMechanism {
+    /// Whether the lamp is lit, for tooling reading lamps as outputs
+    pub fn is_lit(blk: &Block) -> bool {
+        matches!(blk.mechanism, Some(MechanismKind::RedstoneLamp { lit: true }))
+    }
+
+    fn set_lit(chunks: &mut Chunks, x: i64, y: i64, blk: Block, lit: bool) {
+        chunks.set_block(x, y, Some(Block { mechanism: Some(MechanismKind::RedstoneLamp { lit }), ..blk }));
+        chunks.mark_dirty(x, y);
+    }
+}
+
+impl Mechanism for RedstoneLampMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Signal
+    }
+
+    /// Lights as soon as it is powered and goes out `LAMP_OFF_DELAY` game ticks after losing
+    /// power, staying lit if it is powered again meanwhile.
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        let powered = is_powered(ctx.chunks, x, y);
+        let lit = Self::is_lit(&blk);
+        if powered && !lit {
+            Self::set_lit(ctx.chunks, x, y, blk, true);
+        } else if !powered && lit {
+            let delay = LAMP_OFF_DELAY - INPUT_LATENCY;
+            ctx.listeners.schedule_tick(x, y, false, delay, TickPriority::Normal);
+        }
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        if Self::is_lit(&blk) && !is_powered(ctx.chunks, x, y) {
+            Self::set_lit(ctx.chunks, x, y, blk, false);
+        }
+    }
+
+    /// Blocks moved or reshaped next to the lamp can power it without sending it a signal
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _sides: [bool; 4]) {
+        self.on_input_change(ctx, x, y, blk, false);
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        if Self::is_lit(blk) { 1 } else { 0 }
+    }
+
+    fn inspect(&self, blk: &Block) -> Option<String> {
+        Some((if Self::is_lit(blk) { "lit" } else { "off" }).to_string())
+    }
+}
+
+pub struct CopperBulbMechanism;
+
+impl Mechanism for CopperBulbMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Signal
+    }
+
+    /// Toggles on a rising edge of its input, however long the input then stays on.
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        let (lit, was_powered) = if let Some(MechanismKind::CopperBulb { lit, powered }) = blk.mechanism {
+            (lit, powered)
+        } else {
+            return;
+        };
+        let powered = is_powered(ctx.chunks, x, y);
+        if powered == was_powered {
+            return;
+        }
+        let lit = if powered { !lit } else { lit };
+        let mechanism = Some(MechanismKind::CopperBulb { lit, powered });
+        ctx.chunks.set_block(x, y, Some(Block { mechanism, ..blk }));
+        ctx.chunks.mark_dirty(x, y);
+        alert_comparators_behind(ctx.chunks, ctx.listeners, x, y);
+    }
+
+    /// Blocks moved next to the bulb can power it without sending it a signal
+    fn on_neighbour_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _sides: [bool; 4]) {
+        self.on_input_change(ctx, x, y, blk, false);
+    }
+
+    fn output_strength(&self, _blk: &Block) -> u8 {
+        0
+    }
+
+    /// Comparators read full strength from a lit bulb
+    fn analog_output(&self, blk: &Block) -> Option<u8> {
+        Some(if let Some(MechanismKind::CopperBulb { lit: true, .. }) = blk.mechanism { 15 } else { 0 })
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        if let Some(MechanismKind::CopperBulb { lit: true, .. }) = blk.mechanism { 1 } else { 0 }
+    }
+
+    fn inspect(&self, blk: &Block) -> Option<String> {
+        let lit = matches!(blk.mechanism, Some(MechanismKind::CopperBulb { lit: true, .. }));
+        Some((if lit { "lit" } else { "off" }).to_string())
+    }
+}
+
+/// Highest period the clock can be set to, in game ticks
+pub const CLOCK_MAX_TICKS: u8 = 200;
+
+pub struct SignalSourceMechanism;
+
+impl SignalSourceMechanism {
+    fn strength(blk: &Block) -> u8 {
+        if let Some(MechanismKind::SignalSource { strength }) = blk.mechanism { strength } else { 0 }
+    }
+}
+
+impl Mechanism for SignalSourceMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Interaction
+    }
+
+    /// Drives its outputs at the configured strength, held one above like a comparator so a
+    /// strength of 0 still tells the outputs it changed
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        let signal = Self::strength(&blk) + 1;
+        let previous = get_signal(&blk) + 1;
+        if signal == get_signal(&blk) {
+            return;
+        }
+        ctx.emit(x, y, 0, previous);
+        ctx.emit(x, y, signal, previous);
+    }
+
+    fn output_strength(&self, blk: &Block) -> u8 {
+        get_signal(blk).saturating_sub(1).min(15)
+    }
+
+    fn render_state(&self, blk: &Block) -> usize {
+        if Self::strength(blk) > 0 { 1 } else { 0 }
+    }
+
+    fn inspect(&self, blk: &Block) -> Option<String> {
+        Some(format!("strength {}", Self::strength(blk)))
+    }
+
+    fn on_place(&self, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        listeners.turn_mechanism_on(x, y, &blk);
+    }
+
+    fn settings(&self, blk: &Block) -> Vec<Setting> {
+        vec![Setting { name: "strength", value: Self::strength(blk), min: 0, max: 15 }]
+    }
+
+    fn set_setting(&self, blk: Block, _idx: usize, value: u8) -> Block {
+        Block { mechanism: Some(MechanismKind::SignalSource { strength: value }), ..blk }
+    }
+}
+
+pub struct ClockMechanism;
+
+impl ClockMechanism {
+    fn periods(blk: &Block) -> (u8, u8) {
+        if let Some(MechanismKind::Clock { on_ticks, off_ticks }) = blk.mechanism {
+            (on_ticks, off_ticks)
+        } else {
+            (1, 1)
+        }
+    }
+}
+
+impl Mechanism for ClockMechanism {
+    fn phase(&self) -> TickPhase {
+        TickPhase::Interaction
+    }
+
+    /// Starts a fresh on period, so a changed setting applies straight away
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        ctx.listeners.scheduled_ticks.cancel(x, y);
+        if get_signal(&blk) == 0 {
+            ctx.power(x, y);
+        }
+        let (on_ticks, _) = Self::periods(&blk);
+        ctx.listeners.schedule_tick(x, y, false, on_ticks as u64, TickPriority::Normal);
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, _powered: bool) {
+        let (on_ticks, off_ticks) = Self::periods(&blk);
+        let delay = if get_signal(&blk) > 0 {
+            ctx.depower(x, y);
+            off_ticks
+        } else {
+            ctx.power(x, y);
+            on_ticks
+        };
+        ctx.listeners.schedule_tick(x, y, false, delay as u64, TickPriority::Normal);
+    }
+
+    fn inspect(&self, blk: &Block) -> Option<String> {
+        let (on_ticks, off_ticks) = Self::periods(blk);
+        Some(format!("on {on_ticks}, off {off_ticks} game ticks"))
+    }
+
+    fn on_place(&self, listeners: &mut EventListeners, x: i64, y: i64, blk: Block) {
+        listeners.turn_mechanism_on(x, y, &blk);
+    }
+
+    fn settings(&self, blk: &Block) -> Vec<Setting> {
+        let (on_ticks, off_ticks) = Self::periods(blk);
+        vec![
+            Setting { name: "on ticks", value: on_ticks, min: 1, max: CLOCK_MAX_TICKS },
+            Setting { name: "off ticks", value: off_ticks, min: 1, max: CLOCK_MAX_TICKS }
+        ]
+    }
+
+    fn set_setting(&self, blk: Block, idx: usize, value: u8) -> Block {
+        let (on_ticks, off_ticks) = Self::periods(&blk);
+        let (on_ticks, off_ticks) = if idx == 0 { (value, off_ticks) } else { (on_ticks, value) };
+        Block { mechanism: Some(MechanismKind::Clock { on_ticks, off_ticks }), ..blk }
+    }
+}
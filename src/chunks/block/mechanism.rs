@@ -1,474 +1,328 @@
 pub use super::*;
 use std::collections::VecDeque;
+use std::sync::RwLock;
 
-fn get_extended(chunks: &mut Chunks, x: i128, y: i128) -> Option<&mut bool> {
-    let blk = chunks.get_block(x, y);
+pub fn set_extended(chunks: &mut Chunks, x: i64, y: i64, extended: bool) {
     if
-        let Some(Block { mechanism: Some(MechanismKind::Piston { ref mut extended, .. }), .. }) =
-            blk
+        let Some(mut blk @ Block { mechanism: Some(MechanismKind::Piston { sticky, .. }), .. }) =
+            chunks.get_block(x, y)
     {
-        Some(extended)
-    } else {
-        None
+        blk.mechanism = Some(MechanismKind::Piston { extended, sticky });
+        chunks.set_block(x, y, Some(blk));
     }
 }
 
-fn get_movable(chunks: &mut Chunks, x: i128, y: i128) -> Option<&mut bool> {
-    let blk = chunks.get_block(x, y);
-    if let Some(Block { movable, .. }) = blk {
-        Some(movable)
-    } else {
+/// Makes a piston immovable while it is extended or busy, and movable again once it is done
+pub fn set_movable(chunks: &mut Chunks, x: i64, y: i64, movable: bool) {
+    if let Some(blk) = chunks.get_block(x, y) {
+        let push_reaction = if movable { PushReaction::Normal } else { PushReaction::Immovable };
+        chunks.set_block(x, y, Some(Block { push_reaction, ..blk }));
+    }
+}
+
+/// The listener a component waits in. Phases run in declaration order every tick, so
+/// player input settles before observers fire, and blocks finish moving before
+/// torches, repeaters and comparators react to the result.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TickPhase {
+    Interaction,
+    StateChange,
+    Movement,
+    Signal,
+}
+
+impl TickPhase {
+    pub fn iter() -> impl Iterator<Item = TickPhase> {
+        [TickPhase::Interaction, TickPhase::StateChange, TickPhase::Movement, TickPhase::Signal]
+            .iter()
+            .copied()
+    }
+}
+
+/// Why a component is being executed. `bool` is whether its input is powered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MechanismEvent {
+    /// A neighbour changed the signal reaching the component
+    InputChange(bool),
+    /// A tick the component scheduled came due, with its latest input
+    ScheduledTick(bool),
+    /// Blocks next to the component changed, flagged by port index
+    NeighbourChange([bool; 4]),
+}
+
+/// Everything a component may touch while it executes.
+pub struct MechanismContext<'a> {
+    pub chunks: &'a mut Chunks,
+    pub listeners: &'a mut EventListeners,
+    pub propagation_queue: &'a mut PropagationQueue,
+    pub calculations: &'a mut u32,
+    pub texture_to_block_map: &'a HashMap<TextureName, Block>,
+}
+
+impl MechanismContext<'_> {
+    /// Drives the outputs of the component at (x, y) at `strength`, `previous` being the
+    /// strength they carried before.
+    pub fn emit(&mut self, x: i64, y: i64, strength: u8, previous: u8) {
+        propagate_signal_at(
+            self.chunks,
+            x,
+            y,
+            None,
+            strength,
+            previous,
+            None,
+            self.listeners,
+            self.propagation_queue,
+            self.calculations
+        )
+    }
+
+    pub fn power(&mut self, x: i64, y: i64) {
+        self.emit(x, y, 16, 16)
+    }
+
+    pub fn depower(&mut self, x: i64, y: i64) {
+        self.emit(x, y, 0, 17)
+    }
+}
+
+/// A value the player can edit on a component from the settings panel
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Setting {
+    pub name: &'static str,
+    pub value: u8,
+    pub min: u8,
+    pub max: u8,
+}
+
+/// Behaviour of a redstone component. `blk` is always a copy of the block as it was when
+/// the engine picked it up, so implementations write changes back through `chunks`.
+pub trait Mechanism: Send + Sync {
+    /// Which listener the component is queued in
+    fn phase(&self) -> TickPhase;
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool);
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, blk: Block, powered: bool) {
+        self.on_input_change(ctx, x, y, blk, powered)
+    }
+
+    /// Called when a neighbouring block changes, `sides` being flagged by port index
+    fn on_neighbour_change(&self, _ctx: &mut MechanismContext, _x: i64, _y: i64, _blk: Block, _sides: [bool; 4]) {}
+
+    /// Strength, 0 to 15, a comparator reads from the component
+    fn output_strength(&self, blk: &Block) -> u8 {
+        if let Some(Redstone { signal, .. }) = blk.redstone { signal.min(15) } else { 0 }
+    }
+
+    /// Level a comparator measures from behind the component in place of its signal, for
+    /// containers and other blocks with contents
+    fn analog_output(&self, _blk: &Block) -> Option<u8> {
+        None
+    }
+
+    /// Index of the sprite to draw in the block's atlas
+    fn render_state(&self, blk: &Block) -> usize {
+        if let Some(Redstone { signal, .. }) = blk.redstone {
+            if signal > 0 {
+                return 1;
+            }
+        }
+        0
+    }
+
+    /// Details the inspector shows next to the component's name
+    fn inspect(&self, _blk: &Block) -> Option<String> {
         None
     }
+
+    /// Called when the player right-clicks the component
+    fn on_interact(&self, _chunks: &mut Chunks, _listeners: &mut EventListeners, _x: i64, _y: i64, _blk: Block) {}
+
+    /// Called once the component is placed, not when a piston moves it
+    fn on_place(&self, _listeners: &mut EventListeners, _x: i64, _y: i64, _blk: Block) {}
+
+    /// Values edited from the settings panel. Right-clicking a component that has some opens
+    /// the panel instead of calling `on_interact`.
+    fn settings(&self, _blk: &Block) -> Vec<Setting> {
+        Vec::new()
+    }
+
+    /// The block with setting `idx` changed to `value`, which is within its bounds
+    fn set_setting(&self, blk: Block, _idx: usize, _value: u8) -> Block {
+        blk
+    }
+}
+
+/// Identifies a component added with `register_mechanism`. Saved by name, so ids may differ
+/// between runs as long as the same components get registered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct MechanismId(u16);
+
+static REGISTERED_MECHANISMS: RwLock<Vec<(&'static str, &'static dyn Mechanism)>> = RwLock::new(
+    Vec::new()
+);
+
+/// Adds a component defined outside this crate. Blocks with the returned kind as their
+/// mechanism are executed through `mechanism`; `state` in the kind is free for the component
+/// to use. Registering a name twice returns the existing kind.
+pub fn register_mechanism(name: &'static str, mechanism: &'static dyn Mechanism) -> MechanismKind {
+    let mut registered = REGISTERED_MECHANISMS.write().unwrap();
+    let idx = registered
+        .iter()
+        .position(|(registered_name, _)| *registered_name == name)
+        .unwrap_or_else(|| {
+            registered.push((name, mechanism));
+            registered.len() - 1
+        });
+    MechanismKind::Registered { id: MechanismId(idx as u16), state: 0 }
+}
+
+fn registered_id(name: &str) -> Option<MechanismId> {
+    REGISTERED_MECHANISMS.read()
+        .unwrap()
+        .iter()
+        .position(|(registered_name, _)| *registered_name == name)
+        .map(|idx| MechanismId(idx as u16))
 }
 
+/// The kind of the component registered as `name`, with its state cleared.
+pub fn registered_mechanism(name: &str) -> Option<MechanismKind> {
+    registered_id(name).map(|id| MechanismKind::Registered { id, state: 0 })
+}
+
+pub fn get_mechanism(kind: MechanismKind) -> &'static dyn Mechanism {
+    match kind {
+        MechanismKind::RedstoneTorch | MechanismKind::BurntOutTorch => &TorchMechanism,
+        MechanismKind::Repeater { .. } => &RepeaterMechanism,
+        MechanismKind::Piston { .. } => &PistonMechanism,
+        MechanismKind::Observer => &ObserverMechanism,
+        MechanismKind::Lever => &LeverMechanism,
+        MechanismKind::Button | MechanismKind::WoodenButton => &ButtonMechanism,
+        MechanismKind::Comparator { .. } => &ComparatorMechanism,
+        MechanismKind::Container { .. } => &ContainerMechanism,
+        MechanismKind::MovingBlock => &MovingBlockMechanism,
+        MechanismKind::RedstoneLamp { .. } => &RedstoneLampMechanism,
+        MechanismKind::CopperBulb { .. } => &CopperBulbMechanism,
+        MechanismKind::SignalSource { .. } => &SignalSourceMechanism,
+        MechanismKind::Clock { .. } => &ClockMechanism,
+        MechanismKind::Registered { id, .. } => REGISTERED_MECHANISMS.read().unwrap()[id.0 as usize].1,
+    }
+}
+
+impl Serialize for MechanismId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REGISTERED_MECHANISMS.read().unwrap()[self.0 as usize].0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MechanismId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        registered_id(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown mechanism {name}")))
+    }
+}
+
+/// Strength of the signal a block gives off, 0 to 15, as read by comparators.
+pub fn output_strength(blk: &Block) -> u8 {
+    match blk.mechanism {
+        Some(kind) => get_mechanism(kind).output_strength(blk),
+        None => if let Some(Redstone { signal, .. }) = blk.redstone { signal.min(15) } else { 0 },
+    }
+}
+
+/// Details the inspector shows about a component.
+pub fn inspect(blk: &Block) -> Option<String> {
+    blk.mechanism.and_then(|kind| get_mechanism(kind).inspect(blk))
+}
+
+/// Values the settings panel edits on a component.
+pub fn settings(blk: &Block) -> Vec<Setting> {
+    blk.mechanism.map_or(Vec::new(), |kind| get_mechanism(kind).settings(blk))
+}
+
+/// Sets setting `idx` of the component at (x, y), clamped to its bounds, and runs the component
+/// again so the new value takes effect.
+pub fn configure(chunks: &mut Chunks, listeners: &mut EventListeners, x: i64, y: i64, idx: usize, value: u8) {
+    let blk = if let Some(blk @ Block { mechanism: Some(_), .. }) = chunks.get_block(x, y) {
+        blk
+    } else {
+        return;
+    };
+    let setting = if let Some(setting) = settings(&blk).get(idx).copied() {
+        setting
+    } else {
+        return;
+    };
+    let mechanism = get_mechanism(blk.mechanism.unwrap());
+    let blk = mechanism.set_setting(blk, idx, value.clamp(setting.min, setting.max));
+    chunks.set_block(x, y, Some(blk));
+    chunks.mark_dirty(x, y);
+    listeners.turn_mechanism_on(x, y, &blk);
+}
+
+/// Level a comparator measures from a block with contents.
+pub fn analog_output(blk: &Block) -> Option<u8> {
+    blk.mechanism.and_then(|kind| get_mechanism(kind).analog_output(blk))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn execute_mechanism(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
-    on: bool,
+    x: i64,
+    y: i64,
+    event: MechanismEvent,
     listeners: &mut EventListeners,
-    mut commands: &mut Commands,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32,
     texture_to_block_map: &HashMap<TextureName, Block>
 ) {
-    let maybe_blk = chunks.get_block(x, y);
-    let blk = if let Some(blk) = maybe_blk {
+    let blk = if let Some(blk) = chunks.get_block(x, y) {
         blk
     } else {
         return;
     };
-    let blk_ref = blk.clone();
-
-    let Block { orientation, mechanism, redstone, movable, .. } = blk;
-    let mechanism_kind = if let Some(mechanism_kind) = mechanism {
-        mechanism_kind
+    let mechanism = if let Some(kind) = blk.mechanism {
+        get_mechanism(kind)
     } else {
         return;
     };
 
-    let orientation = *orientation;
-    let redstone = *redstone;
-
-    match mechanism_kind {
-        MechanismKind::RedstoneTorch => {
-            let signal = if let Some(Redstone { signal, .. }) = redstone {
-                signal
-            } else {
-                return;
-            };
-            if on && signal > 0 {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    0,
-                    17,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                )
-            } else if !on && signal <= 0 {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    16,
-                    16,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                )
-            }
-        }
-        MechanismKind::Piston { ref mut extended, sticky } => {
-            let rs = if let Some(rs) = redstone {
-                rs
-            } else {
-                return;
-            };
-            let is_sticky = *sticky;
-            let piston_head = if is_sticky { STICKY_PISTON_HEAD } else { PISTON_HEAD };
-            let mut traversed = HashSet::new();
-            *movable = false;
-            //println!("{x} {y} executing with signal {}", rs.signal);
-            if rs.signal > 0 && !*extended {
-                let (next_x, next_y) = orientation.get_next_coord(x, y);
-                let affected_blocks = get_power(chunks, next_x, next_y, orientation, 12);
-                let moved = move_blocks(
-                    chunks,
-                    next_x,
-                    next_y,
-                    orientation,
-                    &affected_blocks,
-                    listeners,
-                    &mut commands,
-                    &image_assets,
-                    query,
-                    propagation_queue,
-                    calculations,
-                    &mut traversed,
-                    orientation.get_opposing(),
-                    texture_to_block_map
-                );
-                if moved {
-                    if let Some(extended) = get_extended(chunks, x, y) {
-                        *extended = true;
-                    }
-
-                    place(
-                        chunks,
-                        piston_head,
-                        orientation,
-                        next_x,
-                        next_y,
-                        listeners,
-                        &mut commands,
-                        &image_assets,
-                        query,
-                        propagation_queue,
-                        calculations,
-                        texture_to_block_map
-                    );
-                    listeners.update_entity(x, y);
-                } else {
-                    if let Some(movable) = get_movable(chunks, x, y) {
-                        *movable = true;
-                    }
-                    listeners.turn_mechanism_on(x, y, &blk_ref);
-                }
-            } else if *extended && rs.signal <= 0 {
-                *extended = false;
-
-                listeners.update_entity(x, y);
-                let (next_x, next_y) = orientation.get_next_coord(x, y);
-                let next_block = chunks.get_block(next_x, next_y);
-
-                if let Some(blk) = next_block {
-                    if blk.texture_name == piston_head.texture_name {
-                        destroy(
-                            chunks,
-                            next_x,
-                            next_y,
-                            listeners,
-                            &mut commands,
-                            &image_assets,
-                            query,
-                            propagation_queue,
-                            calculations
-                        );
-                    }
-                }
-                if is_sticky {
-                    let (next_next_x, next_next_y) = orientation.get_next_coord(next_x, next_y);
-                    let pull_dir = orientation.get_opposing();
-                    let affected_blocks = get_power(chunks, next_next_x, next_next_y, pull_dir, 12);
-                    move_blocks(
-                        chunks,
-                        next_next_x,
-                        next_next_y,
-                        pull_dir,
-                        &affected_blocks,
-                        listeners,
-                        &mut commands,
-                        &image_assets,
-                        query,
-                        propagation_queue,
-                        calculations,
-                        &mut traversed,
-                        pull_dir,
-                        texture_to_block_map
-                    );
-                }
-                if let Some(movable) = get_movable(chunks, x, y) {
-                    *movable = true;
-                }
-            } else {
-                if *extended {
-                    *movable = false;
-                } else {
-                    *movable = true;
-                }
-            }
-        }
-        MechanismKind::Repeater { countdown, tick } => {
-            let signal = if let Some(Redstone { signal, .. }) = redstone {
-                signal
-            } else {
-                return;
-            };
-
-            if *countdown < 0 {
-                if (on && signal <= 0) || (!on && signal > 0) {
-                    *countdown = *tick;
-                }
-            }
-
-            if *countdown > 0 {
-                *countdown -= 1;
-                if on {
-                    listeners.turn_mechanism_on(x, y, blk);
-                } else {
-                    listeners.turn_mechanism_off(x, y, blk);
-                }
-            } else if *countdown == 0 {
-                *countdown -= 1;
-                if signal <= 0 {
-                    propagate_signal_at(
-                        chunks,
-                        x,
-                        y,
-                        None,
-                        16,
-                        16,
-                        None,
-                        listeners,
-                        propagation_queue,
-                        calculations
-                    );
-                    if !on {
-                        listeners.turn_mechanism_off(x, y, &blk_ref);
-                    }
-                } else if !on {
-                    propagate_signal_at(
-                        chunks,
-                        x,
-                        y,
-                        None,
-                        0,
-                        17,
-                        None,
-                        listeners,
-                        propagation_queue,
-                        calculations
-                    );
-                }
-            }
-        }
-        MechanismKind::Observer => {
-            let signal = if let Some(Redstone { signal, .. }) = redstone {
-                signal
-            } else {
-                return;
-            };
-            if on && signal <= 0 {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    16,
-                    16,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                );
-
-                listeners.turn_mechanism_off(x, y, &blk_ref);
-            } else {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    0,
-                    17,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                );
-            }
-        }
-        MechanismKind::Lever => {
-            let signal = if let Some(Redstone { signal, .. }) = redstone {
-                signal
-            } else {
-                return;
-            };
-            if signal > 0 {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    0,
-                    17,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                )
-            } else {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    16,
-                    16,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                )
-            }
-        }
-        MechanismKind::Button => {
-            let signal = if let Some(Redstone { signal, .. }) = redstone {
-                signal
-            } else {
-                return;
-            };
-            if signal > 0 {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    0,
-                    17,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                )
-            } else {
-                propagate_signal_at(
-                    chunks,
-                    x,
-                    y,
-                    None,
-                    16,
-                    16,
-                    None,
-                    listeners,
-                    propagation_queue,
-                    calculations
-                );
-                listeners.turn_mechanism_off(x, y, &blk_ref)
-            }
-        }
-
-        MechanismKind::Comparator { mode } => {
-            let mode = *mode;
-            let rs = if let Some(rs) = redstone {
-                rs
-            } else {
-                return;
-            };
-            let rear_port = orientation.get_opposing();
-            let rear_power = {
-                let (rear_x, rear_y) = rear_port.get_next_coord(x, y);
-                if
-                    let Some(Block { redstone: Some(Redstone { signal, .. }), .. }) =
-                        chunks.get_block_ref(rear_x, rear_y)
-                {
-                    *signal
-                } else {
-                    0
-                }
-            };
-
-            let signal = match mode {
-                ComparatorModes::Compare => {
-                    let (_, max_prev, _) = get_max_prev(chunks, x, y);
-                    if rear_power >= max_prev {
-                        rear_power
-                    } else {
-                        0
-                    }
-                }
-                ComparatorModes::Subtract => {
-                    let mut max_side_signal = 0;
-                    for (idx, port) in rs.input_ports.iter().enumerate() {
-                        let side_port = Orientation::port_idx_to_orientation(idx);
-                        if *port && side_port != rear_port {
-                            let (side_x, side_y) = side_port.get_next_coord(x, y);
-                            let side_signal = if
-                                let Some(Block { redstone: Some(Redstone { signal, .. }), .. }) =
-                                    chunks.get_block_ref(side_x, side_y)
-                            {
-                                *signal
-                            } else {
-                                0
-                            };
-
-                            if side_signal > max_side_signal {
-                                max_side_signal = side_signal;
-                            }
-                        }
-                    }
-                    if rear_power >= max_side_signal {
-                        rear_power - max_side_signal
-                    } else {
-                        0
-                    }
-                }
-            };
+    if let MechanismEvent::InputChange(powered) = event {
+        listeners.scheduled_ticks.update_input(x, y, powered);
+    }
 
-            propagate_signal_at(
-                chunks,
-                x,
-                y,
-                None,
-                0,
-                rs.signal + 1,
-                None,
-                listeners,
-                propagation_queue,
-                calculations
-            );
-            propagate_signal_at(
-                chunks,
-                x,
-                y,
-                None,
-                std::cmp::min(signal + 1, 16),
-                rs.signal + 1,
-                None,
-                listeners,
-                propagation_queue,
-                calculations
-            )
-        }
+    let mut ctx = MechanismContext {
+        chunks,
+        listeners,
+        propagation_queue,
+        calculations,
+        texture_to_block_map,
+    };
+    match event {
+        MechanismEvent::InputChange(powered) => mechanism.on_input_change(&mut ctx, x, y, blk, powered),
+        MechanismEvent::ScheduledTick(powered) => mechanism.on_scheduled_tick(&mut ctx, x, y, blk, powered),
+        MechanismEvent::NeighbourChange(sides) => mechanism.on_neighbour_change(&mut ctx, x, y, blk, sides),
     }
 }
 
-fn move_blocks(
+/// Starts moving the blocks a piston at `piston` pushes or pulls from (x, y), as found by
+/// `get_power`. Returns false if the block at (x, y) cannot move.
+#[allow(clippy::too_many_arguments)]
+pub fn move_blocks(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
+    x: i64,
+    y: i64,
     orientation: Orientation,
-    affected_blocks: &HashSet<(i128, i128)>,
+    affected_blocks: &HashSet<(i64, i64)>,
     listeners: &mut EventListeners,
-    mut commands: &mut Commands,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32,
-    traversed: &mut HashSet<(i128, i128)>,
+    traversed: &mut HashSet<(i64, i64)>,
     from: Orientation,
-    texture_to_block_map: &HashMap<TextureName, Block>
+    piston: (i64, i64)
 ) -> bool {
-    let maybe_blk = chunks.get_block(x, y);
-    let blk = if let Some(blk) = maybe_blk {
-        if blk.movable {
-            *blk
-        } else {
-            return false;
-        }
-    } else {
-        return true;
-    };
+    let blk = if let Some(blk) = chunks.get_block(x, y) { blk } else { return true };
 
     if !affected_blocks.contains(&(x, y)) {
         return false;
@@ -478,6 +332,26 @@ fn move_blocks(
         return false;
     }
 
+    // Blocks in line with the push are pushed, the rest are dragged along by sticky blocks or
+    // pulled by a sticky piston
+    let pushed = from == orientation.get_opposing();
+    match blk.push_reaction {
+        PushReaction::Normal => {}
+        PushReaction::Immovable => {
+            return false;
+        }
+        PushReaction::Destroy if pushed => {
+            destroy(chunks, x, y, listeners, propagation_queue, calculations);
+            traversed.insert((x, y));
+            return true;
+        }
+        PushReaction::PullResistant if pushed => {}
+        // pulled or dragged, both stay where they are, neither moved nor broken
+        PushReaction::Destroy | PushReaction::PullResistant => {
+            return false;
+        }
+    }
+
     let (next_x, next_y) = orientation.get_next_coord(x, y);
     let moved = move_blocks(
         chunks,
@@ -486,41 +360,22 @@ fn move_blocks(
         orientation,
         affected_blocks,
         listeners,
-        &mut commands,
-        &image_assets,
-        query,
         propagation_queue,
         calculations,
         traversed,
         orientation.get_opposing(),
-        texture_to_block_map
+        piston
     );
 
     if moved {
-        if
-            place(
-                chunks,
-                blk,
-                blk.orientation,
-                next_x,
-                next_y,
-                listeners,
-                &mut commands,
-                &image_assets,
-                query,
-                propagation_queue,
-                calculations,
-                texture_to_block_map
-            )
-        {
+        if chunks.get_block(next_x, next_y).is_none() {
+            let ticks = listeners.scheduled_ticks.detach(x, y);
+            place_moving(chunks, listeners, blk, (x, y), next_x, next_y, piston, ticks);
             destroy(
                 chunks,
                 x,
                 y,
                 listeners,
-                &mut commands,
-                &image_assets,
-                query,
                 propagation_queue,
                 calculations
             );
@@ -529,10 +384,14 @@ fn move_blocks(
         traversed.insert((next_x, next_y));
         traversed.insert((x, y));
 
-        if blk.sticky {
+        if blk.sticky.is_sticky() {
             for neighbor_orientation in Orientation::iter() {
                 let (next_x, next_y) = neighbor_orientation.get_next_coord(x, y);
+                let sticks = chunks
+                    .get_block(next_x, next_y)
+                    .is_some_and(|neighbor| blk.sticky.sticks_to(neighbor.sticky));
                 if
+                    sticks &&
                     neighbor_orientation != orientation &&
                     neighbor_orientation != from &&
                     !traversed.contains(&(next_x, next_y))
@@ -544,14 +403,11 @@ fn move_blocks(
                         orientation,
                         affected_blocks,
                         listeners,
-                        &mut commands,
-                        &image_assets,
-                        query,
                         propagation_queue,
                         calculations,
                         traversed,
                         neighbor_orientation.get_opposing(),
-                        texture_to_block_map
+                        piston
                     );
                 }
             }
@@ -561,42 +417,153 @@ fn move_blocks(
     moved
 }
 
-fn get_power(
+/// Game ticks a block moved by a piston spends between cells after the game tick its move
+/// started on. It lands first thing on the game tick after those.
+pub const PISTON_MOVE_TICKS: u64 = 2;
+
+/// A block on its way into a cell. The cell holds a `MOVING_BLOCK` placeholder meanwhile, which
+/// is neither solid nor conductive.
+#[derive(Debug, Clone)]
+pub struct MovingBlock {
+    pub blk: Block,
+    /// The cell it moves out of
+    pub from: (i64, i64),
+    /// Game tick the move started on
+    pub start: u64,
+    /// The piston moving it
+    pub piston: (i64, i64),
+    ticks: DetachedTicks,
+}
+
+impl MovingBlock {
+    /// Renumbers the pending tick of the moving component after its scheduler was merged
+    pub fn shift_ticks(&mut self, shift: OrderShift) {
+        shift.apply_detached(&mut self.ticks);
+    }
+
+    /// Moves the move and the pending tick of the moving component `by` game ticks later
+    pub fn postpone(&mut self, by: u64) {
+        self.start += by;
+        self.ticks.postpone(by);
+    }
+}
+
+/// Puts `blk`, leaving the cell `from`, on its way into (x, y), which must be empty. It lands
+/// once `PISTON_MOVE_TICKS` more game ticks have run, or as soon as its piston acts again.
+#[allow(clippy::too_many_arguments)]
+pub fn place_moving(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
+    listeners: &mut EventListeners,
+    blk: Block,
+    from: (i64, i64),
+    x: i64,
+    y: i64,
+    piston: (i64, i64),
+    ticks: DetachedTicks
+) {
+    chunks.set_block(x, y, Some(MOVING_BLOCK));
+    chunks.mark_dirty(x, y);
+    listeners.moving_blocks.insert((x, y), MovingBlock {
+        blk,
+        from,
+        start: listeners.scheduled_ticks.game_tick(),
+        piston,
+        ticks,
+    });
+    // Counted from the current game tick, so pistons acting on the last moving game tick still
+    // find it moving
+    listeners.schedule_tick(x, y, false, PISTON_MOVE_TICKS + 1, TickPriority::ExtremelyHigh);
+    alert_neighbours(x, y, chunks, listeners);
+}
+
+/// Lands the block moving into (x, y) with all its state, connecting it to its new neighbours.
+/// A component landing is told that all its neighbours changed, so observers fire and pistons
+/// recheck their power once moved.
+pub fn finish_moving(
+    chunks: &mut Chunks,
+    x: i64,
+    y: i64,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32,
+    texture_to_block_map: &HashMap<TextureName, Block>
+) {
+    let moving = if let Some(moving) = listeners.moving_blocks.remove(&(x, y)) {
+        moving
+    } else {
+        return;
+    };
+    listeners.scheduled_ticks.cancel(x, y);
+    chunks.set_block(x, y, None);
+    place_moved(
+        chunks,
+        moving.blk,
+        x,
+        y,
+        listeners,
+        propagation_queue,
+        calculations,
+        texture_to_block_map
+    );
+    listeners.scheduled_ticks.attach(x, y, moving.ticks);
+    for orientation in Orientation::iter() {
+        listeners.change_state(x, y, orientation, chunks);
+    }
+}
+
+/// Cells a push starting at (x, y) affects, or none when the push cannot happen: a block in
+/// the way is immovable, or it would move more than `push_limit` blocks. Blocks broken by the
+/// push do not count towards the limit, and blocks a sticky block cannot drag are left behind.
+pub fn get_power(
+    chunks: &Chunks,
+    x: i64,
+    y: i64,
     orientation: Orientation,
-    strength: u32
-) -> HashSet<(i128, i128)> {
-    let mut queue = VecDeque::from([(x, y, strength)]);
-    let mut traversed: HashSet<(i128, i128)> = HashSet::new();
+    push_limit: u32
+) -> HashSet<(i64, i64)> {
+    let mut queue = VecDeque::from([(x, y, true)]);
+    let mut traversed: HashSet<(i64, i64)> = HashSet::new();
+    let mut moved = 0;
 
-    while queue.len() > 0 {
-        let (x, y, strength) = queue.pop_front().unwrap();
-        traversed.insert((x, y));
-        if let Some(Block { movable: true, sticky, .. }) = chunks.get_block_ref(x, y) {
-            let (next_x, next_y) = orientation.get_next_coord(x, y);
-            if let Some(_) = chunks.get_block_ref(next_x, next_y) {
-                if !traversed.contains(&(next_x, next_y)) && strength > 0 {
-                    queue.push_back((next_x, next_y, strength - 1));
-                }
+    while let Some((x, y, pushed)) = queue.pop_front() {
+        if traversed.contains(&(x, y)) {
+            continue;
+        }
+        let blk = if let Some(blk) = chunks.get_block(x, y) { blk } else { continue };
+        let stays = blk.push_reaction == PushReaction::Immovable;
+        if pushed && stays {
+            // a moving block cannot go through it
+            return HashSet::new();
+        }
+        if
+            stays ||
+            blk.push_reaction == PushReaction::Destroy ||
+            (!pushed && blk.push_reaction == PushReaction::PullResistant)
+        {
+            if pushed {
+                traversed.insert((x, y));
             }
-            if *sticky {
-                for adj in Orientation::iter() {
-                    let (next_x, next_y) = adj.get_next_coord(x, y);
-                    if let Some(_) = chunks.get_block_ref(next_x, next_y) {
-                        if !traversed.contains(&(next_x, next_y)) && strength > 0 {
-                            queue.push_back((next_x, next_y, strength - 1));
-                        }
+            continue;
+        }
+
+        traversed.insert((x, y));
+        moved += 1;
+        if moved > push_limit {
+            return HashSet::new();
+        }
+        let (next_x, next_y) = orientation.get_next_coord(x, y);
+        queue.push_back((next_x, next_y, true));
+        if blk.sticky.is_sticky() {
+            for adj in Orientation::iter() {
+                let (next_x, next_y) = adj.get_next_coord(x, y);
+                if let Some(neighbor) = chunks.get_block(next_x, next_y) {
+                    if adj != orientation && blk.sticky.sticks_to(neighbor.sticky) {
+                        queue.push_back((next_x, next_y, false));
                     }
                 }
             }
         }
     }
 
-    if traversed.len() > 12 {
-        return HashSet::new();
-    }
-
-    return traversed;
+    traversed
 }
@@ -3,11 +3,16 @@ mod redstone;
 pub use redstone::*;
 mod mechanism;
 pub use mechanism::*;
+mod components;
+pub use components::*;
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Block {
-    pub movable: bool,
-    pub sticky: bool,
+    pub sticky: Stickiness,
+    #[serde(default)]
+    pub push_reaction: PushReaction,
+    #[serde(default)]
+    pub conductivity: Conductivity,
     pub orientation: Orientation,
     pub texture_name: TextureName,
     pub symmetric: bool,
@@ -15,7 +20,88 @@ pub struct Block {
     pub mechanism: Option<MechanismKind>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+impl Block {
+    /// Whether the block passes power on like a solid block. Only plain blocks conduct, so
+    /// components from saves that predate `conductivity` are not taken for conductors.
+    pub fn is_conductive(&self) -> bool {
+        self.conductivity == Conductivity::Conductive &&
+            matches!(self.redstone, Some(Redstone { kind: None, .. }))
+    }
+}
+
+/// How a block passes power on, like vanilla's solid and transparent blocks.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Conductivity {
+    /// Solid blocks. Strongly powered by a source they power dust and components next to
+    /// them at full strength, weakly powered by dust they power components only
+    #[default]
+    Conductive,
+    /// See-through blocks like glass, which are never powered
+    Transparent,
+    /// Full blocks that are never powered either, like pistons and redstone blocks
+    NonConductive,
+}
+
+/// Which blocks a block drags along when it moves. Sticky blocks of different groups do not
+/// stick to each other, so honey and slime can sit side by side in one contraption.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+pub enum Stickiness {
+    #[default]
+    None,
+    Slime,
+    Honey,
+}
+
+impl Stickiness {
+    pub fn is_sticky(&self) -> bool {
+        *self != Stickiness::None
+    }
+
+    /// Whether a block of this group drags a neighbour of group `other` along
+    pub fn sticks_to(&self, other: Stickiness) -> bool {
+        self.is_sticky() && (other == Stickiness::None || other == *self)
+    }
+}
+
+/// Saves from before stickiness groups store a bool, sticky blocks were all slime then.
+impl<'de> Deserialize<'de> for Stickiness {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoredStickiness {
+            Sticky(bool),
+            Group(String),
+        }
+
+        match StoredStickiness::deserialize(deserializer)? {
+            StoredStickiness::Sticky(true) => Ok(Stickiness::Slime),
+            StoredStickiness::Sticky(false) => Ok(Stickiness::None),
+            StoredStickiness::Group(name) =>
+                match name.as_str() {
+                    "None" => Ok(Stickiness::None),
+                    "Slime" => Ok(Stickiness::Slime),
+                    "Honey" => Ok(Stickiness::Honey),
+                    _ => Err(serde::de::Error::custom(format!("unknown stickiness {}", name))),
+                }
+        }
+    }
+}
+
+/// How a block responds to pistons, like vanilla's push reactions. A piston is `Immovable`
+/// while it is extended or busy and `Normal` otherwise.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum PushReaction {
+    #[default]
+    Normal,
+    /// Never moved, like obsidian
+    Immovable,
+    /// Broken when pushed, left in place when pulled
+    Destroy,
+    /// Pushed but never pulled, and does not stick to slime, like glazed terracotta
+    PullResistant,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Redstone {
     pub signal: u8,
     pub signal_type: Option<SignalType>,
@@ -26,12 +112,19 @@ pub struct Redstone {
     pub output_ports: [bool; 4],
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum MechanismKind {
     RedstoneTorch,
+    /// A redstone torch that toggled too often, off until it recovers
+    BurntOutTorch,
     Repeater {
-        countdown: i8,
         tick: i8,
+        /// Held by a powered repeater or comparator facing into one of its sides
+        #[serde(default)]
+        locked: bool,
+        /// Whether the rear input is powered, so an unlocked repeater can catch up with it
+        #[serde(default)]
+        input: bool,
     },
     Piston {
         extended: bool,
@@ -39,24 +132,53 @@ pub enum MechanismKind {
     },
     Observer,
     Lever,
+    /// A stone button
     Button,
-    Comparator{mode: ComparatorModes}
+    WoodenButton,
+    Comparator{mode: ComparatorModes},
+    Container {
+        items: u16,
+    },
+    /// Holds the cell a piston is moving a block into
+    MovingBlock,
+    RedstoneLamp {
+        lit: bool,
+    },
+    /// Toggles `lit` each time its input turns on
+    CopperBulb {
+        lit: bool,
+        /// Whether the input was powered when last checked, to spot it turning on
+        powered: bool,
+    },
+    /// Debug source giving off `strength` on every side
+    SignalSource {
+        strength: u8,
+    },
+    /// Debug clock, on for `on_ticks` game ticks then off for `off_ticks`
+    Clock {
+        on_ticks: u8,
+        off_ticks: u8,
+    },
+    Registered {
+        id: MechanismId,
+        state: u8,
+    },
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum ComparatorModes{
     Subtract,
     Compare
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum RedstoneKind {
     Mechanism,
     Dust,
     Block,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum SignalType {
     Strong(bool),
     Weak(bool),
@@ -80,7 +202,7 @@ impl Orientation {
         }
     }
 
-    pub fn get_next_coord(&self, x: i128, y: i128) -> (i128, i128) {
+    pub fn get_next_coord(&self, x: i64, y: i64) -> (i64, i64) {
         match self {
             Orientation::Up => (x - 1, y),
             Orientation::Right => (x, y + 1),
@@ -129,9 +251,6 @@ impl Orientation {
     }
 }
 
-#[derive(Component)]
-pub struct BlockComponent;
-
 pub fn toggle_port(redstone: &mut Redstone, orientation: Orientation, on: bool) {
     let Redstone { output_ports, .. } = redstone;
     let idx = orientation.to_port_idx();
@@ -1,9 +1,119 @@
 pub use super::*;
+use std::collections::VecDeque;
 
+const MAX_SIGNAL: usize = 16;
+
+#[derive(Clone, Debug)]
+struct PropagationArgs {
+    x: i64,
+    y: i64,
+    input_signal: u8,
+    from_port: Option<Orientation>,
+    previous_signal: u8,
+    prev_signal_type: Option<SignalType>,
+}
+
+/// Work queue for the signal solver.
+///
+/// Depowering jobs (`input_signal == 0`) are drained first in FIFO order. Cells that lost
+/// their signal are then re-seeded from their strongest remaining neighbour, and powering
+/// jobs are processed strongest first, so every cell is settled at its final strength the
+/// first time it is expanded.
+#[derive(Resource, Debug)]
+pub struct PropagationQueue {
+    depower: VecDeque<PropagationArgs>,
+    power: [VecDeque<PropagationArgs>; MAX_SIGNAL + 1],
+    repower: Vec<(i64, i64)>,
+    visited: HashSet<(i64, i64)>,
+}
+
+impl Default for PropagationQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropagationQueue {
+    pub fn new() -> PropagationQueue {
+        PropagationQueue {
+            depower: VecDeque::new(),
+            power: Default::default(),
+            repower: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    pub fn append(
+        &mut self,
+        x: i64,
+        y: i64,
+        input_signal: u8,
+        from_port: Option<Orientation>,
+        previous_signal: u8,
+        prev_signal_type: Option<SignalType>
+    ) {
+        let job = PropagationArgs {
+            x,
+            y,
+            input_signal,
+            from_port,
+            previous_signal,
+            prev_signal_type,
+        };
+
+        if input_signal == 0 {
+            self.depower.push_back(job);
+        } else {
+            self.power[std::cmp::min(input_signal as usize, MAX_SIGNAL)].push_back(job);
+        }
+    }
+
+    fn pop_strongest(&mut self) -> Option<PropagationArgs> {
+        for bucket in self.power.iter_mut().rev() {
+            if let Some(job) = bucket.pop_front() {
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depower.is_empty() &&
+            self.repower.is_empty() &&
+            self.power.iter().all(|bucket| bucket.is_empty())
+    }
+
+    /// Runs every queued job until the affected cells reach a steady state.
+    pub fn solve(
+        &mut self,
+        chunks: &mut Chunks,
+        listeners: &mut EventListeners,
+        calculations: &mut u32
+    ) {
+        self.visited.clear();
+        loop {
+            if let Some(job) = self.depower.pop_front() {
+                propagate_job(chunks, job, listeners, self, calculations);
+            } else if !self.repower.is_empty() {
+                for (x, y) in std::mem::take(&mut self.repower) {
+                    let (from_port, previous_signal, prev_signal_type) = get_max_prev(chunks, x, y);
+                    let transmitted_signal = if previous_signal > 0 { previous_signal - 1 } else { 0 };
+                    self.append(x, y, transmitted_signal, from_port, previous_signal, prev_signal_type);
+                }
+            } else if let Some(job) = self.pop_strongest() {
+                propagate_job(chunks, job, listeners, self, calculations);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn propagate_signal_at(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
+    x: i64,
+    y: i64,
     from_port: Option<Orientation>,
     input_signal: u8,
     previous_signal: u8,
@@ -12,12 +122,24 @@ pub fn propagate_signal_at(
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32
 ) {
-    if input_signal <= 0 && previous_signal <= 1 {
+    propagation_queue.append(x, y, input_signal, from_port, previous_signal, prev_signal_type);
+    propagation_queue.solve(chunks, listeners, calculations);
+}
+
+fn propagate_job(
+    chunks: &mut Chunks,
+    job: PropagationArgs,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32
+) {
+    let PropagationArgs { x, y, input_signal, from_port, previous_signal, prev_signal_type } = job;
+    if input_signal == 0 && previous_signal <= 1 {
         return;
     }
 
-    let curr_blk = chunks.get_block(x, y);
-    let blk_ref = curr_blk.clone();
+    let mut curr_blk = chunks.get_block(x, y);
+    let blk_ref = curr_blk;
 
     let (
         signal,
@@ -27,7 +149,7 @@ pub fn propagate_signal_at(
         output_ports,
         signal_type_port_mapping,
         is_redstone_component,
-    ) = match *curr_blk {
+    ) = match curr_blk {
         Some(
             Block {
                 redstone: Some(
@@ -63,58 +185,42 @@ pub fn propagate_signal_at(
             return;
         }
 
-        match kind {
-            Some(RedstoneKind::Dust) | Some(RedstoneKind::Block)=> {
-                if let Some(SignalType::Weak(false)) = prev_signal_type {
-                    return;
-                } else if let None = prev_signal_type {
-                    return;
-                }
-            }
-            Some(RedstoneKind::Mechanism) => {
-                if input_signal > 0 {
-                    listeners.turn_mechanism_on(x, y, &blk_ref.unwrap());
-                } else {
-                    listeners.turn_mechanism_off(x, y, &blk_ref.unwrap());
-                }
-            }
-            None => {
-                match prev_signal_type {
-                    Some(SignalType::Weak(false)) | Some(SignalType::Strong(false)) => {
-                        return;
-                    }
-                    _ => {}
-                }
+        // Conductive blocks with a mechanism, like lamps, are told about power they do not
+        // carry on, such as a neighbouring weakly powered block's
+        if kind == Some(RedstoneKind::Mechanism) || blk_ref.unwrap().mechanism.is_some() {
+            if input_signal > 0 {
+                listeners.turn_mechanism_on(x, y, &blk_ref.unwrap());
+            } else {
+                listeners.turn_mechanism_off(x, y, &blk_ref.unwrap());
             }
         }
+
+        if !accepts(&blk_ref.unwrap(), prev_signal_type) {
+            return;
+        }
     }
 
-    *calculations += 1;
-    if *calculations > 10000 {
-        propagation_queue.append(x, y, input_signal, from_port, previous_signal, prev_signal_type);
-        //println!("{x} {y} returned because too many calc");
+    // powering jobs arrive strongest first, so a cell expanded once already holds its
+    // final strength for this pass
+    if input_signal > 0 && propagation_queue.visited.contains(&(x, y)) {
         return;
     }
 
+    *calculations += 1;
+
     if
         input_signal >= *signal ||
         (previous_signal == *signal + 1 && *signal > 0 && input_signal == 0)
     {
-        if let Some(_) = from_port {
-            if input_signal == *signal && input_signal > 0 {
-                //println!("{x} {y} returned because equality");
-                return;
-            }
+        if from_port.is_some() && input_signal == *signal && input_signal > 0 {
+            return;
         }
 
         let output_signal_type = match *signal_type {
             Some(curr_signal_type) => {
                 if let SignalType::Strong(true) = curr_signal_type {
-                    if let Some(_) = from_port {
-                        if is_redstone_component {
-                            return;
-                        }
-                        
+                    if from_port.is_some() && is_redstone_component {
+                        return;
                     }
                 } else if let SignalType::Weak(true) = curr_signal_type {
                     match prev_signal_type {
@@ -159,14 +265,13 @@ pub fn propagate_signal_at(
 
         let current_signal = *signal;
         *signal = input_signal;
+        chunks.set_block(x, y, curr_blk);
+        listeners.entity_map_update.insert((x, y));
 
-        if input_signal == 1 {
-            return
+        if input_signal > 0 {
+            propagation_queue.visited.insert((x, y));
         }
 
-        let transmitted_signal = if input_signal > 0 { input_signal - 1 } else { 0 };
-        //println!("{x} {y} signal is set to {}", *signal);
-        listeners.entity_map_update.insert((x, y));
         for (idx, port) in output_ports.iter().enumerate() {
             if *port {
                 let port_orientation = Orientation::port_idx_to_orientation(idx);
@@ -178,41 +283,91 @@ pub fn propagate_signal_at(
                     port_output_signal_type = signal_type;
                 }
 
-                propagate_signal_at(
+                let (transmitted_signal, previous_signal) = transmitted_into(
                     chunks,
+                    &blk_ref.unwrap(),
+                    next_x,
+                    next_y,
+                    input_signal,
+                    current_signal
+                );
+                if input_signal > 0 && transmitted_signal == 0 {
+                    continue;
+                }
+
+                propagation_queue.append(
                     next_x,
                     next_y,
-                    Some(input_port_orientation),
                     transmitted_signal,
-                    current_signal,
-                    Some(port_output_signal_type),
-                    listeners,
-                    propagation_queue,
-                    calculations
+                    Some(input_port_orientation),
+                    previous_signal,
+                    Some(port_output_signal_type)
                 );
             }
         }
     }
-    if input_signal == 0 && prev_signal_type != None {
-        listeners.repropagate(x, y)
+    if input_signal == 0 && prev_signal_type.is_some() {
+        propagation_queue.repower.push((x, y));
     }
 }
 
-pub fn get_max_prev(
-    chunks: &mut Chunks,
-    x: i128,
-    y: i128
-) -> (Option<Orientation>, u8, Option<SignalType>) {
-    let chunk_blk = chunks.get_maybe_block(x, y);
-    let curr_blk = if let Some(curr_blk) = chunk_blk {
-        curr_blk
+/// Components and redstone blocks hold their output one above its strength, dust and
+/// conductive blocks hold the strength itself. `blk` is the block the power leaves.
+fn transmitted(blk: &Block, signal: u8, previous: u8, undiminished: bool) -> (u8, u8) {
+    if !undiminished {
+        return (signal.saturating_sub(1), previous);
+    }
+    let held_above = matches!(
+        blk.redstone,
+        Some(Redstone { kind: Some(RedstoneKind::Mechanism | RedstoneKind::Block), .. })
+    );
+    if held_above {
+        (signal.saturating_sub(1).min(15), previous.saturating_sub(1).min(15) + 1)
     } else {
-        return (None, 0, None);
+        (signal.min(15), previous.min(15) + 1)
+    }
+}
+
+/// Strength `blk` at `signal`, previously at `previous`, drives into the cell at (x, y), and the
+/// previous strength to tell it. Power entering or leaving a conductive block is not
+/// diminished, like vanilla where a block takes the level of the dust powering it and dust
+/// next to a strongly powered block is at full strength.
+pub fn transmitted_into(chunks: &Chunks, blk: &Block, x: i64, y: i64, signal: u8, previous: u8) -> (u8, u8) {
+    let undiminished =
+        blk.is_conductive() || chunks.get_block(x, y).is_some_and(|next| next.is_conductive());
+    transmitted(blk, signal, previous, undiminished)
+}
+
+/// Whether power leaving a neighbour as `signal_type` gets into `blk`. Dust takes power from
+/// sources and strongly powered blocks, conductive blocks only from sources and dust, and
+/// components from anything powered. Other blocks are never powered.
+fn accepts(blk: &Block, signal_type: Option<SignalType>) -> bool {
+    let kind = if let Some(Redstone { kind, .. }) = blk.redstone {
+        kind
+    } else {
+        return false;
     };
+    match kind {
+        Some(RedstoneKind::Dust) | Some(RedstoneKind::Block) =>
+            !matches!(signal_type, Some(SignalType::Weak(false)) | None),
+        Some(RedstoneKind::Mechanism) => true,
+        None =>
+            blk.is_conductive() &&
+                !matches!(signal_type, Some(SignalType::Weak(false) | SignalType::Strong(false))),
+    }
+}
 
-    let (signal, input_ports, signal_type) = match curr_blk {
-        Some(Block { redstone: Some(Redstone { signal, input_ports, signal_type, .. }), .. }) =>
-            (*signal, input_ports.clone(), *signal_type),
+/// The neighbour driving the strongest signal into (x, y) that the block there accepts, with
+/// the strength it drives and its signal type. A power block keeps its own signal unless a
+/// neighbour drives more into it.
+pub fn get_max_prev(
+    chunks: &Chunks,
+    x: i64,
+    y: i64
+) -> (Option<Orientation>, u8, Option<SignalType>) {
+    let (blk, signal, input_ports, signal_type) = match chunks.get_block(x, y) {
+        Some(blk @ Block { redstone: Some(Redstone { signal, input_ports, signal_type, .. }), .. }) =>
+            (blk, signal, input_ports, signal_type),
         _ => {
             return (None, 0, None);
         }
@@ -221,42 +376,46 @@ pub fn get_max_prev(
     let mut max_signal = signal + 1;
     let mut max_signal_loc: Option<Orientation> = None;
     let mut max_signal_type = signal_type;
+    let keeps_own = matches!(blk.redstone, Some(Redstone { kind: Some(RedstoneKind::Block), .. }));
 
     for (idx, port) in input_ports.iter().enumerate() {
         if *port {
             let port_orientation = Orientation::port_idx_to_orientation(idx);
             let (next_x, next_y) = port_orientation.get_next_coord(x, y);
-            let next_blk = chunks.get_maybe_block(next_x, next_y);
+            let next_blk = chunks.get_block(next_x, next_y);
             if
                 let Some(
-                    Some(
-                        Block {
-                            redstone: Some(
-                                Redstone {
-                                    signal,
-                                    output_ports,
-                                    signal_type,
-                                    signal_type_port_mapping,
-                                    ..
-                                },
-                            ),
-                            ..
-                        },
-                    ),
+                    next @ Block {
+                        redstone: Some(
+                            Redstone {
+                                signal,
+                                output_ports,
+                                signal_type,
+                                signal_type_port_mapping,
+                                ..
+                            },
+                        ),
+                        ..
+                    },
                 ) = next_blk
             {
+                let output_port_signal_type =
+                    signal_type_port_mapping[port_orientation.get_opposing().to_port_idx()];
+                let mut signal_type = signal_type;
+                if let Some(sig_type) = output_port_signal_type {
+                    signal_type = Some(sig_type);
+                }
+                // Counted one up when the power is not diminished on its way in, callers take one
+                // off the strongest neighbour
+                let undiminished = next.is_conductive() || blk.is_conductive();
+                let signal = if undiminished && signal > 0 { transmitted(&next, signal, signal, true).1 } else { signal };
                 if
                     output_ports[port_orientation.get_opposing().to_port_idx()] &&
-                    (*signal >= max_signal || (max_signal_loc == None && *signal > 0))
+                    accepts(&blk, signal_type) &&
+                    (signal >= max_signal || (max_signal_loc.is_none() && signal > 0 && !keeps_own))
                 {
-                    max_signal = *signal;
+                    max_signal = signal;
                     max_signal_loc = Some(port_orientation);
-                    let output_port_signal_type =
-                        signal_type_port_mapping[port_orientation.get_opposing().to_port_idx()];
-                    let mut signal_type = *signal_type;
-                    if let Some(sig_type) = output_port_signal_type {
-                        signal_type = Some(sig_type);
-                    }
                     max_signal_type = signal_type;
                 }
             }
@@ -265,8 +424,8 @@ pub fn get_max_prev(
     (max_signal_loc, max_signal, max_signal_type)
 }
 
-pub fn is_redstone(chunks: &Chunks, x: i128, y: i128, input_port: Orientation) -> bool {
-    let maybe_blk = chunks.get_block_ref(x, y);
+pub fn is_redstone(chunks: &Chunks, x: i64, y: i64, input_port: Orientation) -> bool {
+    let maybe_blk = chunks.get_block(x, y);
     let blk = if let Some(blk) = maybe_blk {
         blk
     } else {
@@ -278,24 +437,13 @@ pub fn is_redstone(chunks: &Chunks, x: i128, y: i128, input_port: Orientation) -
     } else {
         return false;
     };
-    if
-        (redstone.input_ports[input_port.to_port_idx()] ||
-            redstone.output_ports[input_port.to_port_idx()]) &&
+    (redstone.input_ports[input_port.to_port_idx()] ||
+        redstone.output_ports[input_port.to_port_idx()]) &&
         redstone.is_redstone_component
-    {
-        true
-    } else {
-        false
-    }
 }
 
-pub fn get_redstone_dust(chunks: &mut Chunks, x: i128, y: i128) -> Option<&mut Redstone> {
-    let maybe_blk = chunks.get_block(x, y);
-    let blk = if let Some(blk) = maybe_blk {
-        blk
-    } else {
-        return None;
-    };
+pub fn get_redstone_dust(chunks: &Chunks, x: i64, y: i64) -> Option<Redstone> {
+    let blk = chunks.get_block(x, y)?;
 
     let redstone = if let Block { redstone: Some(redstone), .. } = blk {
         redstone
@@ -310,115 +458,140 @@ pub fn get_redstone_dust(chunks: &mut Chunks, x: i128, y: i128) -> Option<&mut R
     Some(redstone)
 }
 
-pub fn update_dust_ports(
+/// Writes back dust read with `get_redstone_dust`.
+pub fn set_redstone_dust(chunks: &mut Chunks, x: i64, y: i64, redstone_dust: Redstone) {
+    if let Some(blk) = chunks.get_block(x, y) {
+        chunks.set_block(x, y, Some(Block { redstone: Some(redstone_dust), ..blk }));
+    }
+}
+
+/// Sides of the dust at (x, y) with a redstone component it connects to.
+fn dust_connections(chunks: &Chunks, x: i64, y: i64) -> [bool; 4] {
+    let mut connections = [false; 4];
+    for orientation in Orientation::iter() {
+        let (next_x, next_y) = orientation.get_next_coord(x, y);
+        connections[orientation.to_port_idx()] = is_redstone(
+            chunks,
+            next_x,
+            next_y,
+            orientation.get_opposing()
+        );
+    }
+    connections
+}
+
+/// Sides dust points into, which are the only blocks it powers. Like vanilla it points into
+/// everything it connects to, a line with a single connection also points away from it, and
+/// dust with no connections keeps its shape: a cross pointing everywhere or a dot pointing
+/// nowhere.
+fn dust_shape(connections: [bool; 4], current: [bool; 4]) -> [bool; 4] {
+    let mut ports = connections;
+    match connections.iter().filter(|connected| **connected).count() {
+        0 if current == [false; 4] => [false; 4],
+        0 => [true; 4],
+        1 => {
+            let idx = connections.iter().position(|connected| *connected).unwrap();
+            ports[(idx + 2) % 4] = true;
+            ports
+        }
+        _ => ports,
+    }
+}
+
+/// Points the dust at (x, y) into `ports`, taking its signal away from the blocks it stops
+/// pointing into and giving it to the new ones.
+fn set_dust_ports(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
+    x: i64,
+    y: i64,
+    ports: [bool; 4],
     listeners: &mut EventListeners,
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32
 ) {
-    let mut last_orientation = Orientation::Up;
-    let mut count = 0;
-    let mut changed: bool = false;
-    for orientation in Orientation::iter() {
-        let (next_x, next_y) = orientation.get_next_coord(x, y);
-        let is_dust_and_open = is_redstone(chunks, next_x, next_y, orientation.get_opposing());
-        let redstone = get_redstone_dust(chunks, x, y);
-        let redstone_dust = if let Some(redstone_dust) = redstone {
-            redstone_dust
-        } else {
-            return;
-        };
-        let initial = redstone_dust.output_ports[orientation.to_port_idx()];
-        toggle_port(redstone_dust, orientation, false);
-        let mut current = false;
-
-        if is_dust_and_open {
-            current = true;
-            toggle_port(redstone_dust, orientation, true);
-            last_orientation = orientation;
-            count += 1;
-        }
+    let mut redstone_dust = if let Some(redstone_dust) = get_redstone_dust(chunks, x, y) {
+        redstone_dust
+    } else {
+        return;
+    };
+    if redstone_dust.output_ports == ports {
+        return;
+    }
+    let previous_ports = redstone_dust.output_ports;
+    redstone_dust.output_ports = ports;
+    set_redstone_dust(chunks, x, y, redstone_dust);
+    chunks.mark_dirty(x, y);
 
-        if current != initial {
-            changed = true;
+    for orientation in Orientation::iter() {
+        let idx = orientation.to_port_idx();
+        if previous_ports[idx] == ports[idx] {
+            continue;
         }
         let (next_x, next_y) = orientation.get_next_coord(x, y);
-        let signal = redstone_dust.signal;
-        let signal_type = redstone_dust.signal_type;
-
-        propagate_signal_at(
+        let signal = if ports[idx] { redstone_dust.signal } else { 0 };
+        let (transmitted_signal, previous_signal) = transmitted_into(
             chunks,
+            &REDSTONE_DUST,
             next_x,
             next_y,
-            Some(orientation.get_opposing()),
-            0,
             signal,
-            signal_type,
-            listeners,
-            propagation_queue,
-            calculations
+            redstone_dust.signal
         );
-    }
-
-    if count == 1 {
-        let redstone = get_redstone_dust(chunks, x, y);
-        let redstone_dust = if let Some(redstone_dust) = redstone {
-            redstone_dust
-        } else {
-            return;
-        };
-        let opposing_port = last_orientation.get_opposing();
-
-        toggle_port(redstone_dust, opposing_port, true);
-    } else if count == 0 {
-        for orientation in Orientation::iter() {
-            let redstone = get_redstone_dust(chunks, x, y);
-            let redstone_dust = if let Some(redstone_dust) = redstone {
-                redstone_dust
-            } else {
-                return;
-            };
-            toggle_port(redstone_dust, orientation, true);
+        if signal > 0 && transmitted_signal == 0 {
+            continue;
         }
-    }
-
-    if changed {
-        let redstone = get_redstone_dust(chunks, x, y);
-        let dust = if let Some(rs) = redstone {
-            rs
-        } else {
-            return;
-        };
-        let prev_signal = dust.signal + 1;
-        propagate_signal_at(
-            chunks,
-            x,
-            y,
-            None,
-            0,
-            prev_signal,
-            None,
-            listeners,
-            propagation_queue,
-            calculations
-        );
-        let prev_redstone = get_max_prev(chunks, x, y);
-
-        let (from_port, previous_signal, prev_signal_type) = prev_redstone;
-        let transmitted_signal = if previous_signal > 0 { previous_signal - 1 } else { 0 };
         propagate_signal_at(
             chunks,
-            x,
-            y,
-            from_port,
+            next_x,
+            next_y,
+            Some(orientation.get_opposing()),
             transmitted_signal,
             previous_signal,
-            prev_signal_type,
+            redstone_dust.signal_type,
             listeners,
             propagation_queue,
             calculations
         );
     }
 }
+
+/// Reshapes the dust at (x, y), if any, after one of its neighbours changed.
+pub fn update_dust_ports(
+    chunks: &mut Chunks,
+    x: i64,
+    y: i64,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32
+) {
+    let redstone_dust = if let Some(redstone_dust) = get_redstone_dust(chunks, x, y) {
+        redstone_dust
+    } else {
+        return;
+    };
+    let ports = dust_shape(dust_connections(chunks, x, y), redstone_dust.output_ports);
+    set_dust_ports(chunks, x, y, ports, listeners, propagation_queue, calculations);
+}
+
+/// Switches dust with no connections between a cross and a dot. Returns false if there is no
+/// such dust at (x, y).
+pub fn toggle_dust_shape(
+    chunks: &mut Chunks,
+    x: i64,
+    y: i64,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32
+) -> bool {
+    let redstone_dust = if let Some(redstone_dust) = get_redstone_dust(chunks, x, y) {
+        redstone_dust
+    } else {
+        return false;
+    };
+    if dust_connections(chunks, x, y) != [false; 4] {
+        return false;
+    }
+    let ports = if redstone_dust.output_ports == [false; 4] { [true; 4] } else { [false; 4] };
+    set_dust_ports(chunks, x, y, ports, listeners, propagation_queue, calculations);
+    true
+}
@@ -3,153 +3,365 @@ pub use super::*;
 mod block;
 pub use block::*;
 
-use bevy::utils::HashMap;
+mod storage;
+pub use storage::*;
+
+use bevy::utils::{ HashMap, HashSet };
+use std::sync::Arc;
+
+pub const CHUNK_SIZE: (i64, i64) = (16, 16);
+const CHUNK_CELLS: usize = (CHUNK_SIZE.0 * CHUNK_SIZE.1) as usize;
+
+/// The part of a cell that changes while a circuit runs. It is kept out of the block
+/// definition, so dust at every strength and components in every state and setting share one
+/// palette entry. Component state is kept apart again, in `Chunk::mechanisms`, since only a
+/// few cells hold a component.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CellState {
+    signal: u8,
+    /// Pistons are immovable while they are extended or busy, which nothing outside the
+    /// piston sees as a change
+    push_reaction: PushReaction,
+}
+
+/// Every distinct block definition placed in the world, stored once with its dynamic state
+/// taken out. Cells refer to definitions by id, 0 being an empty cell. Definitions are never
+/// dropped: with the dynamic state out there are only as many as block types, orientations
+/// and port layouts in use, far fewer than the ids.
+#[derive(Debug, Clone)]
+pub struct BlockPalette {
+    blocks: Vec<Block>,
+    ids: HashMap<Block, u16>,
+}
+
+impl BlockPalette {
+    fn new() -> BlockPalette {
+        BlockPalette {
+            blocks: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
 
-pub const CHUNK_SIZE: (i128, i128) = (16, 16);
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Splits `blk` into its definition, keyed in the palette, its dynamic state and the
+    /// state of its component
+    fn split(blk: Option<Block>) -> (Option<Block>, CellState, Option<MechanismKind>) {
+        let mut state = CellState::default();
+        let mut mechanism = None;
+        let blk = blk.map(|mut blk| {
+            if let Some(redstone) = &mut blk.redstone {
+                state.signal = redstone.signal;
+                redstone.signal = 0;
+            }
+            mechanism = blk.mechanism.take();
+            state.push_reaction = blk.push_reaction;
+            blk.push_reaction = PushReaction::Normal;
+            blk
+        });
+        (blk, state, mechanism)
+    }
+
+    /// The id of a definition already in the palette. `current` is the id already in the
+    /// cell, which is checked first since most writes only change a signal.
+    fn find(&self, blk: Option<Block>, current: u16) -> Option<u16> {
+        let blk = if let Some(blk) = blk {
+            blk
+        } else {
+            return Some(0);
+        };
+        if current != 0 && self.blocks[(current - 1) as usize] == blk {
+            return Some(current);
+        }
+        self.ids.get(&blk).copied()
+    }
 
-pub type Map = [[Option<Block>; CHUNK_SIZE.0 as usize]; CHUNK_SIZE.1 as usize];
-pub type EntityMap = [[Option<Entity>; CHUNK_SIZE.1 as usize]; CHUNK_SIZE.0 as usize];
-#[derive(Debug, Clone, Copy)]
+    fn insert(&mut self, blk: Block) -> Result<u16, PaletteFull> {
+        let id = u16::try_from(self.blocks.len() + 1).map_err(|_| PaletteFull)?;
+        self.blocks.push(blk);
+        self.ids.insert(blk, id);
+        Ok(id)
+    }
+
+    fn decode(&self, id: u16, state: CellState, mechanism: Option<MechanismKind>) -> Option<Block> {
+        if id == 0 {
+            return None;
+        }
+
+        let mut blk = self.blocks[(id - 1) as usize];
+        if let Some(redstone) = &mut blk.redstone {
+            redstone.signal = state.signal;
+        }
+        blk.mechanism = mechanism;
+        blk.push_reaction = state.push_reaction;
+        Some(blk)
+    }
+}
+
+/// Every palette id is taken, so a block with a new definition can't be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteFull;
+
+impl std::fmt::Display for PaletteFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "the block palette is full")
+    }
+}
+
+impl std::error::Error for PaletteFull {}
+
+#[derive(Debug, Clone)]
 pub struct Chunk {
-    pub map: Map,
-    entity_map: EntityMap,
+    /// Palette id of every cell, row by row
+    ids: [u16; CHUNK_CELLS],
+    states: [CellState; CHUNK_CELLS],
+    /// State of the components in the chunk, by cell index. Chunks hold 256 cells, so the
+    /// index fits a byte.
+    mechanisms: HashMap<u8, MechanismKind>,
+    /// Set whenever a cell changes; cleared once the chunk's texture is rebuilt.
+    pub dirty: bool,
 }
 
 impl Chunk {
     fn new() -> Chunk {
         Chunk {
-            map: [[None; CHUNK_SIZE.0 as usize]; CHUNK_SIZE.1 as usize],
-            entity_map: [[None; CHUNK_SIZE.0 as usize]; CHUNK_SIZE.1 as usize],
+            ids: [0; CHUNK_CELLS],
+            states: [CellState::default(); CHUNK_CELLS],
+            mechanisms: HashMap::new(),
+            dirty: true,
         }
     }
+
+    fn is_empty(&self) -> bool {
+        self.ids.iter().all(|id| *id == 0)
+    }
+
+    /// Bytes the chunk takes up, counting the entries allocated for its components but not
+    /// the table's own bookkeeping.
+    pub fn memory_size(&self) -> usize {
+        std::mem::size_of::<Chunk>() +
+            self.mechanisms.capacity() * std::mem::size_of::<(u8, MechanismKind)>()
+    }
+
+    fn decode(&self, idx: usize, palette: &BlockPalette) -> Option<Block> {
+        palette.decode(self.ids[idx], self.states[idx], self.mechanisms.get(&(idx as u8)).copied())
+    }
+
+    /// Every block in the chunk at (chunk_x, chunk_y), with its world coordinates.
+    pub fn blocks<'a>(
+        &'a self,
+        chunk_x: i64,
+        chunk_y: i64,
+        palette: &'a BlockPalette
+    ) -> impl Iterator<Item = ((i64, i64), Block)> + 'a {
+        (0..CHUNK_CELLS).filter_map(move |idx| {
+            let x = chunk_x * CHUNK_SIZE.0 + ((idx as i64) / CHUNK_SIZE.1);
+            let y = chunk_y * CHUNK_SIZE.1 + ((idx as i64) % CHUNK_SIZE.1);
+            self.decode(idx, palette).map(|blk| ((x, y), blk))
+        })
+    }
 }
 
-#[derive(Debug, Resource)]
-pub struct Chunks(pub HashMap<(i128, i128), Chunk>);
+#[derive(Debug, Clone, Resource)]
+pub struct Chunks {
+    pub loaded: HashMap<(i64, i64), Chunk>,
+    /// Shared with the copies made for parallel regions, and only copied when one of them
+    /// adds a definition
+    pub palette: Arc<BlockPalette>,
+    pub settings: WorldSettings,
+    /// Cells whose block changed since their neighbours were last told
+    changed: HashSet<(i64, i64)>,
+    /// Chunks that changed since they were last saved
+    unsaved: HashSet<(i64, i64)>,
+}
+
+impl Default for Chunks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Chunks {
     pub fn new() -> Chunks {
-        Chunks(HashMap::new())
+        Chunks {
+            loaded: HashMap::new(),
+            palette: Arc::new(BlockPalette::new()),
+            settings: WorldSettings::default(),
+            changed: HashSet::new(),
+            unsaved: HashSet::new(),
+        }
+    }
+
+    /// No chunks, but the same palette and settings, so chunks can be copied over from this
+    /// world.
+    pub fn empty_like(&self) -> Chunks {
+        Chunks {
+            loaded: HashMap::new(),
+            palette: self.palette.clone(),
+            settings: self.settings,
+            changed: HashSet::new(),
+            unsaved: HashSet::new(),
+        }
     }
 
-    pub fn from_world_coord(x: i128, y: i128) -> ((i128, i128), (usize, usize)) {
+    /// The chunk holding (x, y) and the index of the cell within it.
+    pub fn from_world_coord(x: i64, y: i64) -> ((i64, i64), usize) {
         let chunk_x = x.div_euclid(CHUNK_SIZE.0);
         let chunk_y = y.div_euclid(CHUNK_SIZE.1);
-        let u = x.rem_euclid(CHUNK_SIZE.0) as usize;
-        let v = y.rem_euclid(CHUNK_SIZE.1) as usize;
+        let u = x.rem_euclid(CHUNK_SIZE.0);
+        let v = y.rem_euclid(CHUNK_SIZE.1);
 
-        return ((chunk_x, chunk_y), (u, v));
+        ((chunk_x, chunk_y), (u * CHUNK_SIZE.1 + v) as usize)
     }
 
-    pub fn get_chunk(&self, x: i128, y: i128) -> Option<&Chunk> {
+    pub fn get_chunk(&self, x: i64, y: i64) -> Option<&Chunk> {
         let ((chunk_x, chunk_y), _) = Chunks::from_world_coord(x, y);
-        return self.0.get(&(chunk_x, chunk_y));
+        self.loaded.get(&(chunk_x, chunk_y))
     }
 
-    pub fn delete_chunk(&mut self, x: i128, y: i128) {
+    pub fn delete_chunk(&mut self, x: i64, y: i64) {
         let ((chunk_x, chunk_y), _) = Chunks::from_world_coord(x, y);
-        let chunk = self.0.get(&(chunk_x, chunk_y));
+        let chunk = self.loaded.get(&(chunk_x, chunk_y));
         if let Some(chk) = chunk {
-            for r in chk.map {
-                for blk in r {
-                    if let Some(_) = blk {
-                        return;
-                    }
-                }
+            if chk.is_empty() {
+                self.loaded.remove(&(chunk_x, chunk_y));
             }
-            self.0.remove(&(chunk_x, chunk_y));
         }
     }
 
-    pub fn create_chunk_at(&mut self, chunk_x: i128, chunk_y: i128) {
-        let chunk = self.0.get(&(chunk_x, chunk_y));
-        if let None = chunk {
-            self.0.insert((chunk_x, chunk_y), Chunk::new());
+    pub fn create_chunk_at(&mut self, chunk_x: i64, chunk_y: i64) {
+        let chunk = self.loaded.get(&(chunk_x, chunk_y));
+        if chunk.is_none() {
+            self.loaded.insert((chunk_x, chunk_y), Chunk::new());
         }
     }
 
-    pub fn create_chunk_at_world(&mut self, x: i128, y: i128) {
+    pub fn create_chunk_at_world(&mut self, x: i64, y: i64) {
         let ((chunk_x, chunk_y), _) = Chunks::from_world_coord(x, y);
         self.create_chunk_at(chunk_x, chunk_y);
     }
 
-    pub fn get_block(&mut self, x: i128, y: i128) -> &mut Option<Block> {
-        self.create_chunk_at_world(x, y);
-        let (chunk_coord, (u, v)) = Chunks::from_world_coord(x, y);
-        let chunk = self.0.get_mut(&chunk_coord);
+    /// Whether (x, y) holds a block, without decoding it
+    pub fn is_occupied(&self, x: i64, y: i64) -> bool {
+        let (chunk_coord, idx) = Chunks::from_world_coord(x, y);
+        self.loaded.get(&chunk_coord).is_some_and(|chunk| chunk.ids[idx] != 0)
+    }
 
-        if let Some(chk) = chunk {
-            let map = &mut chk.map;
-            return &mut map[u][v];
+    /// A copy of the block at (x, y). Changes only take effect once written back with
+    /// `set_block`.
+    pub fn get_block(&self, x: i64, y: i64) -> Option<Block> {
+        let (chunk_coord, idx) = Chunks::from_world_coord(x, y);
+        let chunk = self.loaded.get(&chunk_coord)?;
+        chunk.decode(idx, &self.palette)
+    }
+
+    /// Stores `blk` at (x, y), creating the chunk if a block is placed in an unloaded one.
+    /// Does not mark the chunk dirty. If the palette has no room for a new definition the
+    /// cell keeps the block it had.
+    pub fn set_block(&mut self, x: i64, y: i64, blk: Option<Block>) {
+        let (chunk_coord, idx) = Chunks::from_world_coord(x, y);
+        if blk.is_some() {
+            self.create_chunk_at(chunk_coord.0, chunk_coord.1);
+        }
+        let current = if let Some(chk) = self.loaded.get(&chunk_coord) {
+            chk.ids[idx]
         } else {
-            panic!("Chunk should exist");
+            return;
+        };
+        let (blk_key, state, mechanism) = BlockPalette::split(blk);
+        let id = match self.palette_id(blk_key, current) {
+            Ok(id) => id,
+            Err(error) => {
+                error!("can't set the block at ({x}, {y}): {error}");
+                return;
+            }
+        };
+        if let Some(chk) = self.loaded.get_mut(&chunk_coord) {
+            let old_mechanism = chk.mechanisms.get(&(idx as u8)).copied();
+            let visible = id != chk.ids[idx] ||
+                state.signal != chk.states[idx].signal ||
+                mechanism != old_mechanism;
+            if visible {
+                self.changed.insert((x, y));
+            }
+            if visible || state != chk.states[idx] {
+                self.unsaved.insert(chunk_coord);
+            }
+            chk.ids[idx] = id;
+            chk.states[idx] = state;
+            if let Some(mechanism) = mechanism {
+                chk.mechanisms.insert(idx as u8, mechanism);
+            } else if old_mechanism.is_some() {
+                chk.mechanisms.remove(&(idx as u8));
+            }
         }
     }
 
-    pub fn get_block_ref(&self, x: i128, y: i128) -> Option<&Block> {
-        let (chunk_coord, (u, v)) = Chunks::from_world_coord(x, y);
-        let chunk = self.0.get(&chunk_coord);
-
-        if let Some(chk) = chunk {
-            let map = &chk.map;
-            if let Some(blk) = &map[u][v] {
-                return Some(blk);
-            } else {
-                return None;
-            }
-        } else {
-            return None;
+    /// The palette id of a block definition, adding it if it is new.
+    fn palette_id(&mut self, blk: Option<Block>, current: u16) -> Result<u16, PaletteFull> {
+        if let Some(id) = self.palette.find(blk, current) {
+            return Ok(id);
         }
+        Arc::make_mut(&mut self.palette).insert(blk.unwrap())
     }
 
-    pub fn get_maybe_block(&mut self, x: i128, y: i128) -> Option<&mut Option<Block>> {
-        let (chunk_coord, (u, v)) = Chunks::from_world_coord(x, y);
-        let chunk = self.0.get_mut(&chunk_coord);
+    /// Cells whose block changed since the last call, sorted.
+    pub fn take_changes(&mut self) -> Vec<(i64, i64)> {
+        let mut changes: Vec<(i64, i64)> = self.changed.drain().collect();
+        changes.sort();
+        changes
+    }
 
-        if let Some(chk) = chunk {
-            let map = &mut chk.map;
-            return Some(&mut map[u][v]);
-        } else {
-            None
-        }
+    /// Forgets the changes in a chunk, for blocks that were only loaded back in
+    fn forget_changes_in(&mut self, chunk_x: i64, chunk_y: i64) {
+        self.changed.retain(|(x, y)| Chunks::from_world_coord(*x, *y).0 != (chunk_x, chunk_y));
     }
 
-    pub fn get_entity(&mut self, x: i128, y: i128) -> &mut Option<Entity> {
-        self.create_chunk_at_world(x, y);
-        let (chunk_coord, (u, v)) = Chunks::from_world_coord(x, y);
-        let chunk = self.0.get_mut(&chunk_coord);
+    /// Every loaded block with its world coordinates.
+    pub fn blocks(&self) -> impl Iterator<Item = ((i64, i64), Block)> + '_ {
+        self.loaded
+            .iter()
+            .flat_map(move |((chunk_x, chunk_y), chunk)| {
+                chunk.blocks(*chunk_x, *chunk_y, &self.palette)
+            })
+    }
 
-        if let Some(chk) = chunk {
-            let map = &mut chk.entity_map;
-            return &mut map[u][v];
-        } else {
-            panic!("Chunk should exist");
+    /// Flags the chunk holding (x, y) for re-rendering, dropping it once it is empty.
+    pub fn mark_dirty(&mut self, x: i64, y: i64) {
+        let (chunk_coord, _) = Chunks::from_world_coord(x, y);
+        if let Some(chk) = self.loaded.get_mut(&chunk_coord) {
+            chk.dirty = true;
         }
+        self.delete_chunk(x, y);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn place(
-    mut chunks: &mut Chunks,
+    chunks: &mut Chunks,
     blk: Block,
     mut orientation: Orientation,
-    x: i128,
-    y: i128,
+    x: i64,
+    y: i64,
     listeners: &mut EventListeners,
-    commands: &mut Commands,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32,
     texture_to_block_map: &HashMap<TextureName, Block>
 ) -> bool {
-    let curr = chunks.get_block(x, y);
-    if let Some(_) = curr {
+    if chunks.get_block(x, y).is_some() {
         return false;
     }
 
+    // Blocks saved before they became components start out in the default state
     let mut blk_clone = *texture_to_block_map.get(&blk.texture_name).unwrap();
     if let Block { mechanism: Some(MechanismKind::Piston { .. }), .. } = blk_clone {
-    } else {
+    } else if blk.mechanism.is_some() {
         blk_clone.mechanism = blk.mechanism;
     }
 
@@ -159,43 +371,108 @@ pub fn place(
         orientation = Orientation::Up;
     }
 
-    let redstone = if let Some(redstone) = blk_clone.redstone {
-        Some(Redstone {
-            input_ports: orientation.rotate_ports(redstone.input_ports),
-            output_ports: orientation.rotate_ports(redstone.output_ports),
-            signal_type_port_mapping: orientation.rotate_ports(redstone.signal_type_port_mapping),
-            ..redstone
-        })
-    } else {
-        None
-    };
+    let redstone = blk_clone.redstone.map(|redstone| Redstone {
+        input_ports: orientation.rotate_ports(redstone.input_ports),
+        output_ports: orientation.rotate_ports(redstone.output_ports),
+        signal_type_port_mapping: orientation.rotate_ports(redstone.signal_type_port_mapping),
+        ..redstone
+    });
 
-    *curr = Some(Block {
+    chunks.set_block(x, y, Some(Block {
         orientation,
         redstone,
         ..blk_clone
-    });
+    }));
 
-    if let Some(rs) = redstone {
-        match rs.signal_type {
-            Some(SignalType::Strong(true) | SignalType::Weak(true)) => {
-                update_dust_ports(chunks, x, y, listeners, propagation_queue, calculations);
+    connect_placed(chunks, x, y, redstone, listeners, propagation_queue, calculations);
 
-                for orientation in Orientation::iter() {
-                    let (next_x, next_y) = orientation.get_next_coord(x, y);
-                    update_dust_ports(
-                        chunks,
-                        next_x,
-                        next_y,
-                        listeners,
-                        propagation_queue,
-                        calculations
-                    );
-                    listeners.update_entity(next_x, next_y);
-                    alert_neighbours(x, y, &chunks, listeners);
-                }
+    if let Some(placed @ Block { mechanism: Some(kind), .. }) = chunks.get_block(x, y) {
+        get_mechanism(kind).on_place(listeners, x, y, placed);
+    }
+
+    true
+}
+
+/// Places a block moved by a piston with all its state: orientation, ports, mechanism state and
+/// the signal components drive their outputs with, which carries on from the new cell. Signal
+/// that only passed through the block is worked out again from its new neighbours.
+#[allow(clippy::too_many_arguments)]
+pub fn place_moved(
+    chunks: &mut Chunks,
+    blk: Block,
+    x: i64,
+    y: i64,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32,
+    texture_to_block_map: &HashMap<TextureName, Block>
+) -> bool {
+    if chunks.get_block(x, y).is_some() {
+        return false;
+    }
+
+    *calculations = 0;
+
+    // Pistons hold their input rather than an output, it is read again at the new cell
+    let own_signal = match blk {
+        Block { mechanism: Some(_), redstone: Some(rs), .. } if rs.output_ports.contains(&true) => rs.signal,
+        _ => 0,
+    };
+    // Signal the block starts with when placed, which only constant sources have
+    let base = texture_to_block_map.get(&blk.texture_name).and_then(|base| base.redstone);
+    let signal_type = base.and_then(|rs| rs.signal_type);
+    let signal = base.map_or(0, |rs| rs.signal);
+    let redstone = blk.redstone.map(|rs| Redstone { signal, signal_type, ..rs });
+    chunks.set_block(x, y, Some(Block { redstone, ..blk }));
+
+    connect_placed(chunks, x, y, redstone, listeners, propagation_queue, calculations);
+
+    if own_signal > 0 {
+        propagate_signal_at(
+            chunks,
+            x,
+            y,
+            None,
+            own_signal,
+            own_signal,
+            None,
+            listeners,
+            propagation_queue,
+            calculations
+        );
+        chunks.mark_dirty(x, y);
+    }
+
+    true
+}
+
+/// Hooks a block just written at (x, y) up to its neighbours: dust next to a source turns
+/// towards it and the block takes the signal its neighbours drive into it.
+fn connect_placed(
+    chunks: &mut Chunks,
+    x: i64,
+    y: i64,
+    redstone: Option<Redstone>,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32
+) {
+    if let Some(rs) = redstone {
+        if let Some(SignalType::Strong(true) | SignalType::Weak(true)) = rs.signal_type {
+            update_dust_ports(chunks, x, y, listeners, propagation_queue, calculations);
+
+            for orientation in Orientation::iter() {
+                let (next_x, next_y) = orientation.get_next_coord(x, y);
+                update_dust_ports(
+                    chunks,
+                    next_x,
+                    next_y,
+                    listeners,
+                    propagation_queue,
+                    calculations
+                );
+                listeners.update_entity(next_x, next_y);
             }
-            _ => {}
         }
 
         let (from_port, previous_signal, prev_signal_type) = get_max_prev(chunks, x, y);
@@ -215,43 +492,35 @@ pub fn place(
         );
     }
 
-    update_entity(commands, &mut chunks, x, y, image_assets, query);
-    alert_neighbours(x, y, &chunks, listeners);
-
-    return true;
+    chunks.mark_dirty(x, y);
+    alert_neighbours(x, y, chunks, listeners);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn destroy(
-    mut chunks: &mut Chunks,
-    x: i128,
-    y: i128,
+    chunks: &mut Chunks,
+    x: i64,
+    y: i64,
     listeners: &mut EventListeners,
-    commands: &mut Commands,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     propagation_queue: &mut PropagationQueue,
     calculations: &mut u32
 ) -> bool {
-    let curr_blk = chunks.get_maybe_block(x, y);
+    let curr_blk = chunks.get_block(x, y);
     *calculations = 0;
-    if let Some(mutref) = curr_blk {
-        if
-            let Some(
-                Block { redstone: Some(Redstone { output_ports, signal, signal_type, .. }), .. },
-            ) = mutref
-        {
-            let curr_signal = *signal;
-            let curr_signal_type = *signal_type;
-            let curr_output_ports = *output_ports;
-            *mutref = None;
-            update_entity(commands, &mut chunks, x, y, image_assets, query);
-            alert_neighbours(x, y, &chunks, listeners);
+    if let Some(blk) = curr_blk {
+        if let Block { redstone: Some(Redstone { output_ports, signal, signal_type, .. }), .. } = blk {
+            let curr_signal = signal;
+            let curr_signal_type = signal_type;
+            let curr_output_ports = output_ports;
+            chunks.set_block(x, y, None);
+            chunks.mark_dirty(x, y);
 
             for (idx, port) in curr_output_ports.iter().enumerate() {
                 if *port {
                     let output_port_orientation = Orientation::port_idx_to_orientation(idx);
                     let (next_x, next_y) = output_port_orientation.get_next_coord(x, y);
                     let input_port_orientation = output_port_orientation.get_opposing();
+                    let (_, previous_signal) = transmitted_into(chunks, &blk, next_x, next_y, 0, curr_signal);
 
                     propagate_signal_at(
                         chunks,
@@ -259,7 +528,7 @@ pub fn destroy(
                         next_y,
                         Some(input_port_orientation),
                         0,
-                        curr_signal,
+                        previous_signal,
                         curr_signal_type,
                         listeners,
                         propagation_queue,
@@ -283,17 +552,16 @@ pub fn destroy(
                         calculations
                     );
                     listeners.update_entity(next_x, next_y);
-                    alert_neighbours(x, y, &chunks, listeners);
                 }
             }
         } else {
-            *mutref = None;
-            update_entity(commands, &mut chunks, x, y, image_assets, query);
-            alert_neighbours(x, y, &chunks, listeners);
+            chunks.set_block(x, y, None);
+            chunks.mark_dirty(x, y);
         }
+        alert_neighbours(x, y, chunks, listeners);
     }
 
     listeners.remove_mechanism(x, y);
 
-    return true;
+    true
 }
@@ -0,0 +1,463 @@
+pub use super::*;
+use std::path::PathBuf;
+use bevy_persistent::Storage;
+
+/// Number of ticks a chunk stays loaded after its last redstone activity
+const TICKET_TICKS: u32 = 300;
+
+pub const DEFAULT_LOAD_RADIUS: i64 = 4;
+
+/// Radius in chunks around the camera that is kept loaded. The visible area is always loaded,
+/// even when it is larger than the radius.
+#[derive(Resource)]
+pub struct LoadRadius(pub i64);
+
+/// Chunks with recent redstone activity, with the number of ticks left on their ticket.
+/// A ticket keeps the chunk and its neighbours loaded so that clocks keep running off-screen.
+#[derive(Resource)]
+pub struct ChunkTickets(HashMap<(i64, i64), u32>);
+
+impl Default for ChunkTickets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkTickets {
+    pub fn new() -> ChunkTickets {
+        ChunkTickets(HashMap::new())
+    }
+
+    /// Ages every ticket by one tick and renews the tickets of chunks that changed this tick or
+    /// have work queued or scheduled.
+    pub fn tick(&mut self, listeners: &EventListeners) {
+        self.0.retain(|_, ticks| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+
+        let active = listeners.entity_map_update
+            .iter()
+            .chain(listeners.mechanism_listener.keys())
+            .chain(listeners.redstone_component_listener.keys())
+            .chain(listeners.interactable_component_listener.keys())
+            .chain(listeners.state_component_listener.keys())
+            .chain(listeners.scheduled_ticks.positions());
+
+        for (x, y) in active {
+            let (chunk_coord, _) = Chunks::from_world_coord(*x, *y);
+            self.0.insert(chunk_coord, TICKET_TICKS);
+        }
+    }
+}
+
+/// The blocks of a chunk with their world coordinates, as stored
+type ChunkBlocks = Vec<((i64, i64), Block)>;
+
+/// The work pending in a chunk when it was unloaded, given back when it is loaded again
+struct ParkedWork {
+    /// Queued events by phase
+    events: Vec<(TickPhase, (i64, i64), MechanismEvent)>,
+    ticks: TickScheduler,
+    moving_blocks: Vec<((i64, i64), MovingBlock)>,
+    /// Game tick the chunk was unloaded on
+    since: u64,
+}
+
+impl ParkedWork {
+    fn take(listeners: &mut EventListeners, chunk_x: i64, chunk_y: i64) -> ParkedWork {
+        let in_chunk = |coord: &(i64, i64)| {
+            Chunks::from_world_coord(coord.0, coord.1).0 == (chunk_x, chunk_y)
+        };
+        let mut events = Vec::new();
+        for phase in TickPhase::iter() {
+            listeners.listener_mut(phase).retain(|coord, event| {
+                if in_chunk(coord) {
+                    events.push((phase, *coord, *event));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        let moving: Vec<(i64, i64)> = listeners.moving_blocks
+            .keys()
+            .filter(|coord| in_chunk(coord))
+            .copied()
+            .collect();
+        ParkedWork {
+            events,
+            ticks: listeners.scheduled_ticks.split_off(in_chunk),
+            moving_blocks: moving
+                .into_iter()
+                .map(|coord| (coord, listeners.moving_blocks.remove(&coord).unwrap()))
+                .collect(),
+            since: listeners.scheduled_ticks.game_tick(),
+        }
+    }
+
+    /// Gives the work back, with its ticks as far from due as when the chunk was unloaded
+    fn restore(self, listeners: &mut EventListeners) {
+        let ParkedWork { events, mut ticks, moving_blocks, since } = self;
+        let paused = listeners.scheduled_ticks.game_tick() - since;
+        for (phase, coord, event) in events {
+            listeners.listener_mut(phase).insert(coord, event);
+        }
+        ticks.postpone(paused);
+        let shift = listeners.scheduled_ticks.merge(ticks);
+        for (coord, mut moving) in moving_blocks {
+            moving.postpone(paused);
+            moving.shift_ticks(shift);
+            listeners.moving_blocks.insert(coord, moving);
+        }
+    }
+}
+
+/// Chunks kept out of memory, one storage entry each in `dir`, so a chunk only takes memory
+/// while it is loaded and autosave only rewrites the chunks that changed. On the web the entries
+/// go to local storage instead of files.
+#[derive(Resource)]
+pub struct ChunkStorage {
+    dir: PathBuf,
+    /// Chunks with an entry. Saved as an entry of its own, so a restarted game finds them.
+    index: HashSet<(i64, i64)>,
+    /// Chunks whose entry holds blocks that are not loaded
+    unloaded: HashSet<(i64, i64)>,
+    /// Chunks unloaded during this run, restored exactly as they were left. Other entries are
+    /// placed one block at a time so their ports and signals are recalculated.
+    settled: HashSet<(i64, i64)>,
+    /// Work pending in settled chunks, kept out of the listeners while they are unloaded
+    parked: HashMap<(i64, i64), ParkedWork>,
+    /// Whether nothing was ever saved here
+    new: bool,
+}
+
+impl ChunkStorage {
+    /// Storage in `dir`, with the chunks an earlier run saved there
+    pub fn open(dir: impl Into<PathBuf>) -> ChunkStorage {
+        let dir = dir.into();
+        let index_entry = Self::entry(&dir, "index");
+        let new = !index_entry.occupied();
+        let index: HashSet<(i64, i64)> = if !new {
+            index_entry
+                .read::<Vec<(i64, i64)>>("chunk index", StorageFormat::Json)
+                .unwrap_or_default()
+                .into_iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        ChunkStorage {
+            dir,
+            unloaded: index.clone(),
+            index,
+            settled: HashSet::new(),
+            parked: HashMap::new(),
+            new,
+        }
+    }
+
+    /// The storage entry called `name` in `dir`. Paths under "local" are keys in the browser's
+    /// local storage on the web, like the other save files.
+    fn entry(dir: &Path, name: &str) -> Storage {
+        let path = dir.join(format!("{name}.json"));
+        #[cfg(not(target_family = "wasm"))]
+        {
+            Storage::Filesystem { path }
+        }
+        #[cfg(target_family = "wasm")]
+        {
+            let key = path.strip_prefix("local").unwrap_or(&path).to_string_lossy().into_owned();
+            Storage::LocalStorage { key }
+        }
+    }
+
+    fn chunk_entry(&self, chunk_x: i64, chunk_y: i64) -> Storage {
+        Self::entry(&self.dir, &format!("chunk_{chunk_x}_{chunk_y}"))
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.new
+    }
+
+    /// Whether the chunk has blocks waiting in storage to be loaded
+    pub fn is_unloaded(&self, chunk_x: i64, chunk_y: i64) -> bool {
+        self.unloaded.contains(&(chunk_x, chunk_y))
+    }
+
+    /// Number of chunks waiting in storage
+    pub fn unloaded_len(&self) -> usize {
+        self.unloaded.len()
+    }
+
+    /// Writes the blocks of a whole-world save as chunk entries
+    pub fn store_saved(&mut self, blocks: &[((i64, i64), Block)]) {
+        let mut by_chunk: HashMap<(i64, i64), ChunkBlocks> = HashMap::new();
+        for ((x, y), blk) in blocks.iter() {
+            let (chunk_coord, _) = Chunks::from_world_coord(*x, *y);
+            by_chunk.entry(chunk_coord).or_insert(Vec::new()).push(((*x, *y), *blk));
+        }
+        for ((chunk_x, chunk_y), blocks) in by_chunk {
+            self.write(chunk_x, chunk_y, blocks);
+            self.unloaded.insert((chunk_x, chunk_y));
+            self.settled.remove(&(chunk_x, chunk_y));
+            self.parked.remove(&(chunk_x, chunk_y));
+        }
+        self.write_index();
+    }
+
+    /// Replaces the stored world with the one waiting in `save_data.json` in `dir`, which the
+    /// web importer writes and older versions saved to, then empties the file so it is only
+    /// imported once. Returns whether there was a world to import.
+    pub fn import(&mut self, dir: &Path) -> bool {
+        let entry = Self::entry(dir, "save_data");
+        if !entry.occupied() {
+            return false;
+        }
+        let blocks: ChunkBlocks = entry
+            .read("save data", StorageFormat::Json)
+            .unwrap_or_else(|error| {
+                error!("failed to import the save: {error}");
+                Vec::new()
+            });
+        if blocks.is_empty() {
+            return false;
+        }
+
+        let stored: Vec<(i64, i64)> = self.index.iter().copied().collect();
+        for (chunk_x, chunk_y) in stored {
+            self.write(chunk_x, chunk_y, Vec::new());
+            self.unloaded.remove(&(chunk_x, chunk_y));
+            self.settled.remove(&(chunk_x, chunk_y));
+            self.parked.remove(&(chunk_x, chunk_y));
+        }
+        self.store_saved(&blocks);
+        if let Err(error) = entry.write("save data", StorageFormat::Json, &ChunkBlocks::new()) {
+            error!("failed to empty the imported save: {error}");
+        }
+        true
+    }
+
+    /// Replaces the entry of a chunk. An emptied chunk is dropped from the index, its entry is
+    /// overwritten rather than removed since local storage cannot remove keys here.
+    fn write(&mut self, chunk_x: i64, chunk_y: i64, blocks: ChunkBlocks) {
+        let entry = self.chunk_entry(chunk_x, chunk_y);
+        let written = entry
+            .initialize()
+            .and_then(|_| entry.write("chunk", StorageFormat::Json, &blocks));
+        if let Err(error) = written {
+            error!("failed to save chunk ({chunk_x}, {chunk_y}): {error}");
+        }
+        if blocks.is_empty() {
+            self.index.remove(&(chunk_x, chunk_y));
+        } else {
+            self.index.insert((chunk_x, chunk_y));
+        }
+    }
+
+    fn read(&self, chunk_x: i64, chunk_y: i64) -> ChunkBlocks {
+        self.chunk_entry(chunk_x, chunk_y)
+            .read("chunk", StorageFormat::Json)
+            .unwrap_or_else(|error| {
+                error!("failed to load chunk ({chunk_x}, {chunk_y}): {error}");
+                Vec::new()
+            })
+    }
+
+    fn write_index(&mut self) {
+        self.new = false;
+        let entry = Self::entry(&self.dir, "index");
+        let mut index: Vec<(i64, i64)> = self.index.iter().copied().collect();
+        index.sort();
+        let written = entry
+            .initialize()
+            .and_then(|_| entry.write("chunk index", StorageFormat::Json, &index));
+        if let Err(error) = written {
+            error!("failed to save the chunk index: {error}");
+        }
+    }
+
+    /// The blocks of a loaded chunk as they are saved: blocks still moving are saved where
+    /// they are going.
+    fn chunk_blocks(
+        chunks: &Chunks,
+        listeners: &EventListeners,
+        chunk_x: i64,
+        chunk_y: i64
+    ) -> ChunkBlocks {
+        chunks.loaded.get(&(chunk_x, chunk_y)).map_or(Vec::new(), |chunk| {
+            chunk
+                .blocks(chunk_x, chunk_y, &chunks.palette)
+                .map(|(coord, blk)| {
+                    (coord, listeners.moving_blocks.get(&coord).map_or(blk, |moving| moving.blk))
+                })
+                .collect()
+        })
+    }
+
+    /// Writes every chunk that changed since it was last saved, dropping the entries of chunks
+    /// that were emptied. A chunk recreated by a neighbour before its stored blocks were loaded
+    /// back waits until they are.
+    pub fn save_changed(&mut self, chunks: &mut Chunks, listeners: &EventListeners) {
+        let unsaved: Vec<(i64, i64)> = chunks.unsaved
+            .iter()
+            .filter(|chunk_coord| !self.unloaded.contains(*chunk_coord))
+            .copied()
+            .collect();
+        if unsaved.is_empty() {
+            return;
+        }
+        for (chunk_x, chunk_y) in unsaved {
+            chunks.unsaved.remove(&(chunk_x, chunk_y));
+            let blocks = Self::chunk_blocks(chunks, listeners, chunk_x, chunk_y);
+            if blocks.is_empty() && !self.index.contains(&(chunk_x, chunk_y)) {
+                continue;
+            }
+            self.write(chunk_x, chunk_y, blocks);
+        }
+        self.write_index();
+    }
+
+    /// Frees a loaded chunk, writing it out first if it changed since it was last saved. The
+    /// work pending in it waits with the storage until it is loaded again.
+    pub fn unload(&mut self, chunks: &mut Chunks, listeners: &mut EventListeners, chunk_x: i64, chunk_y: i64) {
+        let blocks = Self::chunk_blocks(chunks, listeners, chunk_x, chunk_y);
+        if chunks.loaded.remove(&(chunk_x, chunk_y)).is_none() {
+            return;
+        }
+        let empty = blocks.is_empty();
+        if chunks.unsaved.remove(&(chunk_x, chunk_y)) {
+            self.write(chunk_x, chunk_y, blocks);
+            self.write_index();
+        }
+        if !empty {
+            self.unloaded.insert((chunk_x, chunk_y));
+            self.settled.insert((chunk_x, chunk_y));
+            self.parked.insert((chunk_x, chunk_y), ParkedWork::take(listeners, chunk_x, chunk_y));
+        }
+    }
+
+    /// Loads the stored blocks of a chunk back in, with the work pending in it when it was
+    /// unloaded
+    pub fn load(
+        &mut self,
+        chunks: &mut Chunks,
+        chunk_x: i64,
+        chunk_y: i64,
+        listeners: &mut EventListeners,
+        propagation_queue: &mut PropagationQueue,
+        texture_to_block_map: &HashMap<TextureName, Block>
+    ) {
+        if !self.unloaded.remove(&(chunk_x, chunk_y)) {
+            return;
+        }
+        let blocks = self.read(chunk_x, chunk_y);
+        let settled = self.settled.remove(&(chunk_x, chunk_y));
+        // blocks a neighbour put in the chunk meanwhile are merged in, so it is saved again
+        let recreated = chunks.loaded.contains_key(&(chunk_x, chunk_y));
+
+        chunks.create_chunk_at(chunk_x, chunk_y);
+        let mut calculations = 0;
+        for ((x, y), blk) in blocks {
+            if settled {
+                chunks.set_block(x, y, Some(blk));
+            } else if
+                blk.texture_name != TextureName::PistonHead &&
+                blk.texture_name != TextureName::StickyPistonHead
+            {
+                place(
+                    chunks,
+                    blk,
+                    blk.orientation,
+                    x,
+                    y,
+                    listeners,
+                    propagation_queue,
+                    &mut calculations,
+                    texture_to_block_map
+                );
+            }
+        }
+
+        if let Some(parked) = self.parked.remove(&(chunk_x, chunk_y)) {
+            parked.restore(listeners);
+        }
+
+        chunks.forget_changes_in(chunk_x, chunk_y);
+        // blocks placed one at a time are saved again with their recalculated state
+        if settled && !recreated {
+            chunks.unsaved.remove(&(chunk_x, chunk_y));
+        }
+        if let Some(chunk) = chunks.loaded.get_mut(&(chunk_x, chunk_y)) {
+            chunk.dirty = true;
+        }
+    }
+}
+
+/// Loads stored chunks around the camera and around ticketed chunks, and unloads every other
+/// chunk. A stored chunk that was recreated by a neighbour reaching into it is always loaded
+/// so its blocks are merged back.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_chunks(
+    mut chunks: ResMut<Chunks>,
+    mut storage: ResMut<ChunkStorage>,
+    tickets: Res<ChunkTickets>,
+    load_radius: Res<LoadRadius>,
+    mut listeners: ResMut<EventListeners>,
+    mut propagation_queue: ResMut<PropagationQueue>,
+    texture_to_block_map: Res<TextureToBlockMap>,
+    q_camera: Query<(&OrthographicProjection, &GlobalTransform), With<Camera>>
+) {
+    let (projection, camera_transform) = if let Ok(camera) = q_camera.get_single() {
+        camera
+    } else {
+        return;
+    };
+    let position = camera_transform.translation();
+    let (x, y, _, _) = get_mouse_coord(position.x, position.y);
+    let ((center_x, center_y), _) = Chunks::from_world_coord(x, y);
+
+    let chunk_width = (CHUNK_SIZE.0 as f32) * BOX_WIDTH;
+    let view_extent = projection.area.width().max(projection.area.height()) / 2.0;
+    let radius = load_radius.0.max((view_extent / chunk_width).ceil() as i64 + 1);
+
+    let mut resident = HashSet::new();
+    for dx in -radius..=radius {
+        for dy in -radius..=radius {
+            resident.insert((center_x + dx, center_y + dy));
+        }
+    }
+    for (chunk_x, chunk_y) in tickets.0.keys() {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                resident.insert((chunk_x + dx, chunk_y + dy));
+            }
+        }
+    }
+
+    let to_load: Vec<(i64, i64)> = resident
+        .iter()
+        .chain(chunks.loaded.keys())
+        .filter(|(chunk_x, chunk_y)| storage.is_unloaded(*chunk_x, *chunk_y))
+        .copied()
+        .collect();
+    for (chunk_x, chunk_y) in to_load {
+        storage.load(
+            &mut chunks,
+            chunk_x,
+            chunk_y,
+            &mut listeners,
+            &mut propagation_queue,
+            &texture_to_block_map.0
+        );
+    }
+
+    let to_unload: Vec<(i64, i64)> = chunks.loaded
+        .keys()
+        .filter(|chunk_coord| !resident.contains(*chunk_coord))
+        .copied()
+        .collect();
+    for (chunk_x, chunk_y) in to_unload {
+        storage.unload(&mut chunks, &mut listeners, chunk_x, chunk_y);
+    }
+}
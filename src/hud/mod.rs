@@ -2,6 +2,9 @@ use bevy::diagnostic::DiagnosticsStore;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 pub use super::*;
 
+mod panel;
+pub use panel::*;
+
 /// Marker to find the container entity so we can show/hide the FPS counter
 #[derive(Component)]
 pub struct FpsRoot;
@@ -19,6 +22,14 @@ pub struct YText;
 #[derive(Component)]
 pub struct TPSText;
 
+/// Marker for the inspector line describing the block under the cursor
+#[derive(Component)]
+pub struct BlockText;
+
+/// Marker for the line listing the world settings
+#[derive(Component)]
+pub struct WorldSettingsText;
+
 pub fn setup_fps_counter(
     mut commands: Commands,
 ) {
@@ -180,7 +191,62 @@ pub fn setup_fps_counter(
             ..Default::default()
         },
     )).id();
-    commands.entity(root).push_children(&[text_fps, y_coord, x_coord, updates_per_second]);
+    let block_info = commands.spawn((
+        BlockText,
+        TextBundle {
+            text: Text::from_sections([
+                TextSection {
+                    value: "block: ".into(),
+                    style: TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    }
+                },
+                TextSection {
+                    value: " N/A".into(),
+                    style: TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    }
+                },
+            ]),
+            ..Default::default()
+        },
+    )).id();
+    let world_settings = commands.spawn((
+        WorldSettingsText,
+        TextBundle {
+            text: Text::from_sections([
+                TextSection {
+                    value: "rules: ".into(),
+                    style: TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    }
+                },
+                TextSection {
+                    value: " N/A".into(),
+                    style: TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    }
+                },
+            ]),
+            ..Default::default()
+        },
+    )).id();
+    commands.entity(root).push_children(&[
+        text_fps,
+        y_coord,
+        x_coord,
+        updates_per_second,
+        block_info,
+        world_settings,
+    ]);
 }
 
 pub fn fps_text_update_system(
@@ -261,7 +327,7 @@ pub fn mouse_pos_update_system(
     {
         let (x, y, _, _) = get_mouse_coord(position.x, position.y);
         for mut text in &mut x_query{
-            let x = -1 * x;
+            let x = -x;
             text.sections[1].value = format!("{x}");
         }
     
@@ -295,4 +361,58 @@ pub fn update_tps_text(
         };
         updates.number_of_updates = 0;
     }
-}
\ No newline at end of file
+}
+
+/// Shows the block under the cursor, with the details its component reports
+pub fn inspector_update_system(
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    chunks: Res<Chunks>,
+    mut block_query: Query<&mut Text, With<BlockText>>
+) {
+    let (camera, camera_transform) = q_camera.single();
+    let blk = q_windows
+        .single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+        .and_then(|position| {
+            let (x, y, _, _) = get_mouse_coord(position.x, position.y);
+            chunks.get_block(x, y)
+        });
+
+    let value = match blk {
+        Some(blk) => match inspect(&blk) {
+            Some(details) => format!("{} ({details})", blk.texture_name.name()),
+            None => blk.texture_name.name().to_string(),
+        },
+        None => " N/A".into(),
+    };
+    for mut text in &mut block_query {
+        text.sections[1].value = value.clone();
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+/// Shows the world settings the keys in `update_world_settings` change
+pub fn world_settings_text_update_system(
+    chunks: Res<Chunks>,
+    mut settings_query: Query<&mut Text, With<WorldSettingsText>>
+) {
+    let settings = chunks.settings;
+    let value = format!(
+        "burnout {} (F2), quasi {} (F3), parallel {} (F4), push limit {} (F5/F6)",
+        on_off(settings.torch_burnout),
+        on_off(settings.quasi_connectivity),
+        on_off(settings.parallel_regions),
+        settings.push_limit
+    );
+    for mut text in &mut settings_query {
+        if text.sections[1].value != value {
+            text.sections[1].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use super::*;
+
+/// The component whose settings the panel edits, opened by right-clicking it
+#[derive(Resource, Default)]
+pub struct SettingsPanel {
+    pub target: Option<(i64, i64)>,
+}
+
+/// Marker for the settings panel container
+#[derive(Component)]
+pub struct PanelRoot;
+
+/// Text showing the value of a setting, by index
+#[derive(Component)]
+pub struct SettingText(usize);
+
+/// Button changing a setting by `step`, ten times as much with shift held
+#[derive(Component)]
+pub struct SettingButton {
+    idx: usize,
+    step: i16,
+}
+
+#[derive(Component)]
+pub struct PanelCloseButton;
+
+const PANEL_FONT_SIZE: f32 = 16.0;
+
+fn panel_text(value: String) -> TextBundle {
+    TextBundle::from_section(value, TextStyle {
+        font_size: PANEL_FONT_SIZE,
+        color: Color::WHITE,
+        ..default()
+    })
+}
+
+fn panel_button(parent: &mut ChildBuilder, label: &str, marker: impl Bundle) {
+    parent
+        .spawn((
+            marker,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(22.0),
+                    height: Val::Px(22.0),
+                    margin: UiRect::horizontal(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::GRAY.with_a(0.8)),
+                ..default()
+            },
+        ))
+        .with_children(|button| {
+            button.spawn(panel_text(label.into()));
+        });
+}
+
+pub fn setup_settings_panel(mut commands: Commands) {
+    commands.spawn((
+        PanelRoot,
+        // lets the panel catch clicks so they do not reach the world under it
+        Interaction::default(),
+        NodeBundle {
+            background_color: BackgroundColor(Color::BLACK.with_a(0.7)),
+            z_index: ZIndex::Global(i32::MAX),
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                left: Val::Percent(1.),
+                bottom: Val::Percent(1.),
+                padding: UiRect::all(Val::Px(6.0)),
+                row_gap: Val::Px(4.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+/// Rebuilds the panel for a newly opened component, keeps the values shown up to date and
+/// closes it once the component is gone
+pub fn settings_panel_update_system(
+    mut commands: Commands,
+    mut panel: ResMut<SettingsPanel>,
+    chunks: Res<Chunks>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut root_query: Query<(Entity, &mut Style), With<PanelRoot>>,
+    mut text_query: Query<(&mut Text, &SettingText)>
+) {
+    let blk = panel.target.and_then(|(x, y)| chunks.get_block(x, y));
+    let current = blk.map_or(Vec::new(), |blk| settings(&blk));
+    if panel.target.is_some() && (current.is_empty() || keyboard_input.just_pressed(KeyCode::Escape)) {
+        panel.target = None;
+    }
+
+    if panel.is_changed() {
+        let (root, mut style) = root_query.single_mut();
+        commands.entity(root).despawn_descendants();
+        if panel.target.is_none() {
+            style.display = Display::None;
+            return;
+        }
+        style.display = Display::Flex;
+        let name = blk.map_or("", |blk| blk.texture_name.name());
+        commands.entity(root).with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(panel_text(name.to_string()));
+                    panel_button(row, "x", PanelCloseButton);
+                });
+            for (idx, setting) in current.iter().enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style { align_items: AlignItems::Center, ..default() },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        panel_button(row, "-", SettingButton { idx, step: -1 });
+                        panel_button(row, "+", SettingButton { idx, step: 1 });
+                        row.spawn((SettingText(idx), panel_text(format!("{}: {}", setting.name, setting.value))));
+                    });
+            }
+        });
+        return;
+    }
+
+    for (mut text, SettingText(idx)) in &mut text_query {
+        if let Some(setting) = current.get(*idx) {
+            text.sections[0].value = format!("{}: {}", setting.name, setting.value);
+        }
+    }
+}
+
+pub fn settings_button_system(
+    mut panel: ResMut<SettingsPanel>,
+    mut chunks: ResMut<Chunks>,
+    mut listeners: ResMut<EventListeners>,
+    keyboard_input: Res<Input<KeyCode>>,
+    setting_buttons: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    close_buttons: Query<&Interaction, (Changed<Interaction>, With<PanelCloseButton>)>
+) {
+    if close_buttons.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        panel.target = None;
+        return;
+    }
+
+    let (x, y) = if let Some(target) = panel.target {
+        target
+    } else {
+        return;
+    };
+    let multiplier = if keyboard_input.pressed(KeyCode::ShiftLeft) { 10 } else { 1 };
+    for (interaction, button) in &setting_buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let current = chunks.get_block(x, y).map_or(Vec::new(), |blk| settings(&blk));
+        if let Some(setting) = current.get(button.idx) {
+            let value = (setting.value as i16 + button.step * multiplier).clamp(0, u8::MAX as i16);
+            configure(chunks.as_mut(), &mut listeners, x, y, button.idx, value as u8);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 mod chunks;
 mod saves;
-use std::{ f32::consts::PI, path::Path };
+use std::path::Path;
 use std::time::Duration;
 use bevy::asset::AssetMetaCheck;
 use wasm_bindgen::prelude::*;
@@ -27,14 +27,37 @@ const BOX_WIDTH: f32 = 48.0;
 mod hud;
 pub use hud::*;
 
+mod render;
+pub use render::*;
+
+mod regions;
+pub use regions::*;
+
+mod registry;
+pub use registry::*;
+
+mod scheduler;
+pub use scheduler::*;
+
+mod settings;
+pub use settings::*;
+
 #[derive(Resource)]
 pub struct EventListeners {
-    pub entity_map_update: HashSet<(i128, i128)>,
-    pub mechanism_listener: HashMap<(i128, i128), bool>,
-    pub repropagation_listener: HashSet<(i128, i128)>,
-    pub redstone_component_listener: HashMap<(i128, i128), bool>,
-    pub interactable_component_listener: HashMap<(i128, i128), bool>,
-    pub state_component_listener: HashMap<(i128, i128), bool>
+    pub entity_map_update: HashSet<(i64, i64)>,
+    pub mechanism_listener: HashMap<(i64, i64), MechanismEvent>,
+    pub redstone_component_listener: HashMap<(i64, i64), MechanismEvent>,
+    pub interactable_component_listener: HashMap<(i64, i64), MechanismEvent>,
+    pub state_component_listener: HashMap<(i64, i64), MechanismEvent>,
+    pub scheduled_ticks: TickScheduler,
+    /// Blocks pistons are moving, by the cell they move into
+    pub moving_blocks: HashMap<(i64, i64), MovingBlock>
+}
+
+impl Default for EventListeners {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EventListeners {
@@ -42,85 +65,71 @@ impl EventListeners {
         EventListeners {
             entity_map_update: HashSet::new(),
             mechanism_listener: HashMap::new(),
-            repropagation_listener: HashSet::new(),
             redstone_component_listener: HashMap::new(),
             interactable_component_listener: HashMap::new(),
-            state_component_listener: HashMap::new()
+            state_component_listener: HashMap::new(),
+            scheduled_ticks: TickScheduler::new(),
+            moving_blocks: HashMap::new()
         }
     }
 
-    pub fn update_entity(&mut self, x: i128, y: i128) {
+    pub fn update_entity(&mut self, x: i64, y: i64) {
         self.entity_map_update.insert((x, y));
     }
 
-    pub fn turn_mechanism_on(&mut self, x: i128, y: i128, blk: &Block) {
-
-        let mechanism = if let Block{mechanism: Some(mech), ..} = blk {
-            mech
-        } else{
-            return
-        };
-
-        match mechanism {
-            MechanismKind::Observer => {
-                self.state_component_listener.insert((x, y), true);
-            }
-            MechanismKind::Lever | MechanismKind::Button => {
-                self.interactable_component_listener.insert((x, y), true);
-            }
-            MechanismKind::Comparator { .. } | MechanismKind::RedstoneTorch | MechanismKind::Repeater { .. } => {
-                self.redstone_component_listener.insert((x, y), true);
-            },
-            MechanismKind::Piston { .. } => {
-                self.mechanism_listener.insert((x, y), true);
-            }
+    pub fn listener_mut(&mut self, phase: TickPhase) -> &mut HashMap<(i64, i64), MechanismEvent> {
+        match phase {
+            TickPhase::Interaction => &mut self.interactable_component_listener,
+            TickPhase::StateChange => &mut self.state_component_listener,
+            TickPhase::Movement => &mut self.mechanism_listener,
+            TickPhase::Signal => &mut self.redstone_component_listener,
         }
     }
 
-    pub fn turn_mechanism_off(&mut self, x: i128, y: i128, blk: &Block) {
+    fn queue(&mut self, x: i64, y: i64, blk: &Block, event: MechanismEvent) {
+        if let Block { mechanism: Some(kind), .. } = blk {
+            let phase = get_mechanism(*kind).phase();
+            self.listener_mut(phase).insert((x, y), event);
+        }
+    }
 
-        let mechanism = if let Block{mechanism: Some(mech), ..} = blk {
-            mech
-        } else{
-            return
-        };
+    pub fn turn_mechanism_on(&mut self, x: i64, y: i64, blk: &Block) {
+        self.queue(x, y, blk, MechanismEvent::InputChange(true));
+    }
 
-        match mechanism {
-            MechanismKind::Observer => {
-                self.state_component_listener.insert((x, y), false);
-            }
-            MechanismKind::Lever | MechanismKind::Button => {
-                self.interactable_component_listener.insert((x, y), false);
-            }
-            MechanismKind::Comparator { .. } | MechanismKind::RedstoneTorch | MechanismKind::Repeater { .. } => {
-                self.redstone_component_listener.insert((x, y), false);
-            },
-            MechanismKind::Piston { .. } => {
-                self.mechanism_listener.insert((x, y), false);
-            }
-        }
+    pub fn turn_mechanism_off(&mut self, x: i64, y: i64, blk: &Block) {
+        self.queue(x, y, blk, MechanismEvent::InputChange(false));
     }
 
-    pub fn remove_mechanism(&mut self, x: i128, y: i128) {
-        self.mechanism_listener.remove(&(x, y));
+    /// Runs the component at (x, y) again in `delay` game ticks, `powered` being its input
+    /// unless it changes in the meantime.
+    pub fn schedule_tick(&mut self, x: i64, y: i64, powered: bool, delay: u64, priority: TickPriority) {
+        self.scheduled_ticks.schedule(x, y, powered, delay, priority);
     }
 
-    pub fn repropagate(&mut self, x: i128, y: i128) {
-        self.repropagation_listener.insert((x, y));
+    pub fn remove_mechanism(&mut self, x: i64, y: i64) {
+        self.mechanism_listener.remove(&(x, y));
+        self.scheduled_ticks.cancel(x, y);
+        self.scheduled_ticks.forget_toggles(x, y);
+        self.moving_blocks.remove(&(x, y));
     }
 
-    pub fn change_state(&mut self, x: i128, y: i128, from_port: Orientation, chunks: &Chunks) {
-        let blk = chunks.get_block_ref(x, y);
-        let orientation = if
-            let Some(Block { mechanism: Some(MechanismKind::Observer), orientation, .. }) = blk
-        {
-            orientation
+    /// Tells the component at (x, y) that the block on its `from_port` side changed. Sides
+    /// changing in the same tick are collected into one event. An input change already queued
+    /// takes precedence, components recheck their neighbours when handling it.
+    pub fn change_state(&mut self, x: i64, y: i64, from_port: Orientation, chunks: &Chunks) {
+        let kind = if let Some(Block { mechanism: Some(kind), .. }) = chunks.get_block(x, y) {
+            kind
         } else {
             return;
         };
 
-        if from_port == orientation.get_opposing() {
-            self.turn_mechanism_on(x, y, &blk.unwrap())
+        let event = self
+            .listener_mut(get_mechanism(kind).phase())
+            .entry((x, y))
+            .or_insert(MechanismEvent::NeighbourChange([false; 4]));
+        if let MechanismEvent::NeighbourChange(sides) = event {
+            sides[from_port.to_port_idx()] = true;
         }
     }
 }
@@ -130,7 +139,7 @@ pub struct SelectedBlock(Option<Block>);
 
 impl SelectedBlock {
     pub fn get_block(&self) -> Option<Block> {
-        return self.0;
+        self.0
     }
 }
 
@@ -140,7 +149,7 @@ fn blk_string_to_block_map() -> HashMap<String, Block> {
     let mut hashmap = HashMap::new();
     for (texture_name, texture) in texture_map.iter(){
         if let Some(blk) = all_block_map.get(texture){
-            hashmap.insert(texture_name.clone(), blk.clone());
+            hashmap.insert(texture_name.clone(), *blk);
         }
         
     }
@@ -148,9 +157,9 @@ fn blk_string_to_block_map() -> HashMap<String, Block> {
     hashmap
 }
 
-#[derive(Resource, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[wasm_bindgen]
-pub struct SaveData(Vec<((i128, i128), Block)>);
+pub struct SaveData(Vec<((i64, i64), Block)>);
 
 #[wasm_bindgen]
 impl SaveData {
@@ -168,12 +177,15 @@ impl SaveData {
                     *tick = (state % 4) as i8;
                 },
                 Block{mechanism: Some(MechanismKind::Comparator{mode}), ..} => {
-                    if state % 2 == 0 {
+                    if state.is_multiple_of(2) {
                         *mode = ComparatorModes::Compare
                     } else{
                         *mode = ComparatorModes::Subtract
                     }
                 },
+                Block{mechanism: Some(MechanismKind::Container { items }), ..} => {
+                    *items = state.min(CONTAINER_CAPACITY as usize) as u16;
+                },
                 _ => {}
             }
             *new_blk
@@ -181,7 +193,7 @@ impl SaveData {
             return
         };
         blk.orientation = Orientation::port_idx_to_orientation(orientation as usize);
-        self.0.push(((x as i128, y as i128), blk))
+        self.0.push(((x, y), blk))
     }
 
     pub fn json_string(&self) -> String {
@@ -189,6 +201,12 @@ impl SaveData {
     }
 }
 
+impl SaveData {
+    pub fn from_chunks(chunks: &Chunks) -> SaveData {
+        SaveData(chunks.blocks().collect())
+    }
+}
+
 
 
 #[derive(Resource)]
@@ -202,78 +220,17 @@ pub struct UpdatesPerSecondTimer {
     pub timer: Timer,
 }
 
-#[derive(Clone, Debug)]
-struct PropagationArgs {
-    x: i128,
-    y: i128,
-    input_signal: u8,
-    from_port: Option<Orientation>,
-    previous_signal: u8,
-    prev_signal_type: Option<SignalType>,
-}
-
-#[derive(Resource, Debug)]
-pub struct PropagationQueue(Vec<PropagationArgs>);
-
-impl PropagationQueue {
-    pub fn append(
-        &mut self,
-        x: i128,
-        y: i128,
-        input_signal: u8,
-        from_port: Option<Orientation>,
-        previous_signal: u8,
-        prev_signal_type: Option<SignalType>
-    ) {
-        self.0.push(PropagationArgs {
-            x,
-            y,
-            input_signal,
-            from_port,
-            previous_signal,
-            prev_signal_type,
-        })
-    }
-
-    fn execute_queue(
-        &mut self,
-        chunks: &mut Chunks,
-        listeners: &mut EventListeners,
-        calculations: &mut u32
-    ) {
-        let queue = self.0.clone();
-        self.0.clear();
-        *calculations = 0;
-        for job in queue.iter() {
-            propagate_signal_at(
-                chunks,
-                job.x,
-                job.y,
-                job.from_port,
-                job.input_signal,
-                job.previous_signal,
-                job.prev_signal_type,
-                listeners,
-                self,
-                calculations
-            );
-        }
-    }
-
-    fn is_empty(&self) -> bool {
-        self.0.len() <= 0
-    }
-}
-
-const SLOW_TICK: f64 = 0.5;
-const TICK: f64 = 0.02;
-const FAST_TICK: f64 = 0.001;
+/// Length of a game tick. Components wait a whole number of game ticks, two per redstone tick.
+const SLOW_TICK: f64 = 0.25;
+const TICK: f64 = 0.01;
+const FAST_TICK: f64 = 0.0005;
 
 #[derive(Resource)]
-pub struct TextureToBlockMap(HashMap<TextureName, Block>);
-const DIRT: Block = Block {
-    movable: true,
-    sticky: false,
+pub struct TextureToBlockMap(pub HashMap<TextureName, Block>);
+pub const DIRT: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Conductive,
     orientation: Orientation::Up,
     texture_name: TextureName::Dirt,
     symmetric: true,
@@ -289,9 +246,22 @@ const DIRT: Block = Block {
     mechanism: None,
 };
 
-const REDSTONE_LAMP: Block = Block {
-    movable: true,
-    sticky: false,
+pub const MOVING_BLOCK: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Immovable,
+    conductivity: Conductivity::Transparent,
+    orientation: Orientation::Up,
+    texture_name: TextureName::MovingBlock,
+    symmetric: true,
+    redstone: None,
+    mechanism: Some(MechanismKind::MovingBlock),
+};
+
+// A solid block that lights up: it conducts power like dirt and its mechanism watches it
+pub const REDSTONE_LAMP: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Conductive,
     orientation: Orientation::Up,
     texture_name: TextureName::RedstoneLamp,
     symmetric: true,
@@ -304,31 +274,13 @@ const REDSTONE_LAMP: Block = Block {
         input_ports: [true, true, true, true],
         output_ports: [true, true, true, true],
     }),
-    mechanism: None,
+    mechanism: Some(MechanismKind::RedstoneLamp { lit: false }),
 };
 
-const WOOL_TEXTURES: [TextureName; 16] = [
-    TextureName::BlackWool,
-    TextureName::BlueWool,
-    TextureName::BrownWool,
-    TextureName::CyanWool,
-    TextureName::GrayWool,
-    TextureName::GreenWool,
-    TextureName::LightBlueWool,
-    TextureName::LightGrayWool,
-    TextureName::LimeWool,
-    TextureName::MagentaWool,
-    TextureName::OrangeWool,
-    TextureName::PinkWool,
-    TextureName::PurpleWool,
-    TextureName::RedWool,
-    TextureName::WhiteWool,
-    TextureName::YellowWool,
-];
-
-const SLIME: Block = Block {
-    movable: true,
-    sticky: true,
+pub const SLIME: Block = Block {
+    sticky: Stickiness::Slime,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Conductive,
     orientation: Orientation::Up,
     texture_name: TextureName::SlimeBlock,
     symmetric: true,
@@ -344,9 +296,10 @@ const SLIME: Block = Block {
     mechanism: None,
 };
 
-const REDSTONE_TORCH: Block = Block {
-    movable: false,
-    sticky: false,
+pub const REDSTONE_TORCH: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Destroy,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::RedstoneTorch,
     symmetric: false,
@@ -367,9 +320,10 @@ const REDSTONE_TORCH: Block = Block {
     mechanism: Some(MechanismKind::RedstoneTorch),
 };
 
-const BUTTON: Block = Block {
-    movable: false,
-    sticky: false,
+pub const BUTTON: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Destroy,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::Button,
     symmetric: true,
@@ -390,9 +344,36 @@ const BUTTON: Block = Block {
     mechanism: Some(MechanismKind::Button),
 };
 
-const LEVER: Block = Block {
-    movable: false,
-    sticky: false,
+pub const WOODEN_BUTTON: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Destroy,
+    conductivity: Conductivity::Transparent,
+    orientation: Orientation::Up,
+    texture_name: TextureName::WoodenButton,
+    symmetric: true,
+    redstone: Some(Redstone {
+        signal: 0,
+        signal_type: Some(SignalType::Strong(true)),
+        is_redstone_component: true,
+        kind: Some(RedstoneKind::Mechanism),
+        signal_type_port_mapping: [
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+        ],
+        input_ports: [false, false, false, false],
+        output_ports: [true, true, true, true],
+    }),
+    mechanism: Some(MechanismKind::WoodenButton),
+};
+
+// Levers, repeaters and comparators are pushed and pulled with their state, where Java would
+// break them like torches and buttons
+pub const LEVER: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::Lever,
     symmetric: true,
@@ -413,9 +394,10 @@ const LEVER: Block = Block {
     mechanism: Some(MechanismKind::Lever),
 };
 
-const REPEATER: Block = Block {
-    movable: false,
-    sticky: false,
+pub const REPEATER: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::Repeater,
     symmetric: false,
@@ -428,12 +410,13 @@ const REPEATER: Block = Block {
         input_ports: [false, false, true, false],
         output_ports: [true, false, false, false],
     }),
-    mechanism: Some(MechanismKind::Repeater { countdown: -1, tick: 0 }),
+    mechanism: Some(MechanismKind::Repeater { tick: 0, locked: false, input: false }),
 };
 
-const COMPARATOR: Block = Block {
-    movable: false,
-    sticky: false,
+pub const COMPARATOR: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::Comparator,
     symmetric: false,
@@ -449,9 +432,10 @@ const COMPARATOR: Block = Block {
     mechanism: Some(MechanismKind::Comparator { mode: ComparatorModes::Subtract }),
 };
 
-const OBSERVER: Block = Block {
-    movable: true,
-    sticky: false,
+pub const OBSERVER: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
     orientation: Orientation::Up,
     texture_name: TextureName::Observer,
     symmetric: false,
@@ -467,9 +451,10 @@ const OBSERVER: Block = Block {
     mechanism: Some(MechanismKind::Observer),
 };
 
-const REDSTONE_DUST: Block = Block {
-    movable: false,
-    sticky: false,
+pub const REDSTONE_DUST: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Destroy,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::RedstoneDust,
     symmetric: true,
@@ -490,9 +475,10 @@ const REDSTONE_DUST: Block = Block {
     mechanism: None,
 };
 
-const PISTON: Block = Block {
-    movable: true,
-    sticky: false,
+pub const PISTON: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
     orientation: Orientation::Up,
     texture_name: TextureName::Piston,
     symmetric: false,
@@ -508,9 +494,10 @@ const PISTON: Block = Block {
     mechanism: Some(MechanismKind::Piston { extended: false, sticky: false }),
 };
 
-const PISTON_HEAD: Block = Block {
-    movable: false,
-    sticky: false,
+pub const PISTON_HEAD: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Immovable,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::PistonHead,
     symmetric: false,
@@ -518,9 +505,10 @@ const PISTON_HEAD: Block = Block {
     mechanism: None,
 };
 
-const STICKY_PISTON: Block = Block {
-    movable: true,
-    sticky: false,
+pub const STICKY_PISTON: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
     orientation: Orientation::Up,
     texture_name: TextureName::StickyPiston,
     symmetric: false,
@@ -536,9 +524,10 @@ const STICKY_PISTON: Block = Block {
     mechanism: Some(MechanismKind::Piston { extended: false, sticky: true }),
 };
 
-const STICKY_PISTON_HEAD: Block = Block {
-    movable: false,
-    sticky: false,
+pub const STICKY_PISTON_HEAD: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Immovable,
+    conductivity: Conductivity::Transparent,
     orientation: Orientation::Up,
     texture_name: TextureName::StickyPistonHead,
     symmetric: false,
@@ -546,9 +535,77 @@ const STICKY_PISTON_HEAD: Block = Block {
     mechanism: None,
 };
 
-const TARGET_BLOCK: Block = Block {
-    movable: true,
-    sticky: false,
+pub const COPPER_BULB: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
+    orientation: Orientation::Up,
+    texture_name: TextureName::CopperBulb,
+    symmetric: true,
+    redstone: Some(Redstone {
+        signal: 0,
+        signal_type: None,
+        is_redstone_component: false,
+        kind: Some(RedstoneKind::Mechanism),
+        signal_type_port_mapping: [None, None, None, None],
+        input_ports: [true, true, true, true],
+        output_ports: [false, false, false, false],
+    }),
+    mechanism: Some(MechanismKind::CopperBulb { lit: false, powered: false }),
+};
+
+pub const SIGNAL_SOURCE: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
+    orientation: Orientation::Up,
+    texture_name: TextureName::SignalSource,
+    symmetric: true,
+    redstone: Some(Redstone {
+        signal: 0,
+        is_redstone_component: true,
+        signal_type: Some(SignalType::Strong(true)),
+        kind: Some(RedstoneKind::Mechanism),
+        signal_type_port_mapping: [
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+        ],
+        input_ports: [false, false, false, false],
+        output_ports: [true, true, true, true],
+    }),
+    mechanism: Some(MechanismKind::SignalSource { strength: 15 }),
+};
+
+pub const CLOCK: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
+    orientation: Orientation::Up,
+    texture_name: TextureName::Clock,
+    symmetric: true,
+    redstone: Some(Redstone {
+        signal: 0,
+        is_redstone_component: true,
+        signal_type: Some(SignalType::Strong(true)),
+        kind: Some(RedstoneKind::Mechanism),
+        signal_type_port_mapping: [
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+            Some(SignalType::Strong(true)),
+        ],
+        input_ports: [false, false, false, false],
+        output_ports: [true, true, true, true],
+    }),
+    mechanism: Some(MechanismKind::Clock { on_ticks: 10, off_ticks: 10 }),
+};
+
+pub const TARGET_BLOCK: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
     orientation: Orientation::Up,
     texture_name: TextureName::TargetBlock,
     symmetric: true,
@@ -569,9 +626,21 @@ const TARGET_BLOCK: Block = Block {
     mechanism: None,
 };
 
-const REDSTONE_BLOCK: Block = Block {
-    movable: true,
-    sticky: false,
+pub const CONTAINER: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Immovable,
+    conductivity: Conductivity::NonConductive,
+    orientation: Orientation::Up,
+    texture_name: TextureName::Container,
+    symmetric: true,
+    redstone: None,
+    mechanism: Some(MechanismKind::Container { items: 0 }),
+};
+
+pub const REDSTONE_BLOCK: Block = Block {
+    sticky: Stickiness::None,
+    push_reaction: PushReaction::Normal,
+    conductivity: Conductivity::NonConductive,
     orientation: Orientation::Up,
     texture_name: TextureName::RedstoneBlock,
     symmetric: true,
@@ -592,17 +661,7 @@ const REDSTONE_BLOCK: Block = Block {
     mechanism: None,
 };
 
-const GLASS: Block = Block{
-    movable: true,
-    sticky: false,
-    orientation: Orientation::Up,
-    texture_name: TextureName::Glass,
-    symmetric: false,
-    redstone: None,
-    mechanism: None,
-};
-
-fn create_all_block_map() -> HashMap<TextureName, Block>{
+pub fn create_all_block_map() -> HashMap<TextureName, Block>{
     let mut  hashmap = HashMap::from([
         (TextureName::Dirt, DIRT),
         (TextureName::RedstoneDust, REDSTONE_DUST),
@@ -615,18 +674,20 @@ fn create_all_block_map() -> HashMap<TextureName, Block>{
         (TextureName::Repeater, REPEATER),
         (TextureName::SlimeBlock, SLIME),
         (TextureName::Button, BUTTON),
+        (TextureName::WoodenButton, WOODEN_BUTTON),
         (TextureName::Lever, LEVER),
         (TextureName::Comparator, COMPARATOR),
         (TextureName::TargetBlock, TARGET_BLOCK),
         (TextureName::RedstoneBlock, REDSTONE_BLOCK),
-        (TextureName::Glass, GLASS),
-        (TextureName::RedstoneLamp, REDSTONE_LAMP)
+        (TextureName::RedstoneLamp, REDSTONE_LAMP),
+        (TextureName::CopperBulb, COPPER_BULB),
+        (TextureName::SignalSource, SIGNAL_SOURCE),
+        (TextureName::Clock, CLOCK),
+        (TextureName::Container, CONTAINER)
     ]);
 
-    for wool in WOOL_TEXTURES{
-        let mut wool_blk = DIRT.clone();
-        wool_blk.texture_name = wool;
-        hashmap.insert(wool, wool_blk);
+    for blk in block_registry().blocks() {
+        hashmap.insert(blk.texture_name, blk);
     }
 
     hashmap
@@ -653,6 +714,7 @@ pub fn run() {
         OBSERVER,
         LEVER,
         BUTTON,
+        WOODEN_BUTTON,
         SLIME,
         PISTON,
         STICKY_PISTON,
@@ -660,79 +722,94 @@ pub fn run() {
         COMPARATOR,
         TARGET_BLOCK,
         REDSTONE_BLOCK,
-        GLASS,
-        REDSTONE_LAMP
+        REDSTONE_LAMP,
+        COPPER_BULB,
+        SIGNAL_SOURCE,
+        CLOCK,
+        CONTAINER
     ];
 
-    for wool in WOOL_TEXTURES{
-        let mut wool_blk = DIRT.clone();
-        wool_blk.texture_name = wool;
-        placeable.push(wool_blk);
-    }
+    placeable.extend(block_registry().blocks());
 
-    let save_data: SaveData = serde_json::from_str(saves::SAVE).unwrap();
+    // A world waiting to be imported replaces the stored one, the built-in world is stored on
+    // the first run
+    let mut storage = ChunkStorage::open(state_dir.join("chunks"));
+    if !storage.import(&state_dir) && storage.is_new() {
+        let save_data: SaveData = serde_json::from_str(saves::SAVE).unwrap();
+        storage.store_saved(&save_data.0);
+    }
 
     App::new()
         .insert_resource(AssetMetaCheck::Never)
         .add_state::<MyStates>()
-        .add_plugins(FrameTimeDiagnosticsPlugin::default())
+        .add_plugins(FrameTimeDiagnosticsPlugin)
         .insert_resource(Time::<Fixed>::from_seconds(TICK))
         .add_plugins(DefaultPlugins)
         .insert_resource(Msaa::Off)
         .insert_resource(Speed(500.))
         .insert_resource(chunks)
         .insert_resource(event_listeners)
-        .insert_resource(PropagationQueue(Vec::new()))
+        .insert_resource(PropagationQueue::new())
+        .insert_resource(ChunkSprites::new())
+        .insert_resource(storage)
+        .insert_resource(ChunkTickets::new())
+        .insert_resource(LoadRadius(DEFAULT_LOAD_RADIUS))
         .insert_resource(SelectedBlock(Some(DIRT)))
+        .insert_resource(SettingsPanel::default())
         .insert_resource(Orientation::Up)
         .insert_resource(Fast(1))
         .insert_resource(TextureToBlockMap(all_blocks))
         .insert_resource(
-            Persistent::<SaveData>
+            Persistent::<WorldSettings>
                 ::builder()
-                .name("save data")
+                .name("world settings")
                 .format(StorageFormat::Json)
-                .path(state_dir.join("save_data.json"))
-                .default(save_data)
+                .path(state_dir.join("world_settings.json"))
+                .default(WorldSettings::default())
                 .build()
-                .expect("failed to initialize game state")
+                .expect("failed to initialize world settings")
         )
         .add_loading_state(
             LoadingState::new(MyStates::AssetLoading)
                 .continue_to_state(MyStates::InGame)
                 .load_collection::<ImageAssets>()
         )
-        .add_systems(Startup, setup_fps_counter)
+        .add_systems(Startup, (setup_fps_counter, setup_settings_panel, load_registry_atlases))
         .add_systems(Update, (fps_text_update_system, fps_counter_showhide))
         .add_systems(OnEnter(MyStates::InGame), init)
         .add_systems(Update, mouse_pos_update_system.run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, inspector_update_system.run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, world_settings_text_update_system.run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, settings_button_system.before(settings_panel_update_system).run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, settings_panel_update_system.run_if(in_state(MyStates::InGame)))
         .add_systems(FixedUpdate, execute_listeners.run_if(in_state(MyStates::InGame)))
-        .add_systems(Update, delayed_redstone_listeners.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, mouse_input.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, update_selected_block.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, move_camera.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, update_orientation.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, autosave.run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, update_world_settings.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, zoom_camera.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, update_tick)
         .add_systems(Update, update_cursor_position.run_if(in_state(MyStates::InGame)))
         .add_systems(Update, update_tps_text.run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, stream_chunks.after(move_camera).run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, render_chunks.after(mouse_input).after(stream_chunks).run_if(in_state(MyStates::InGame)))
+        .add_systems(Update, render_moving_blocks.run_if(in_state(MyStates::InGame)))
         .run()
 }
 
 const AUTOSAVE_INTERVAL_SECONDS: f32 = 10.0;
 const UPDATES_TIMER_INTERVAL_SECONDS: f32 = 5.0;
 
+#[allow(clippy::too_many_arguments)]
 fn init(
     mut commands: Commands,
-    save_data: Res<Persistent<SaveData>>,
-    mut chunks: ResMut<Chunks>,
-    mut listeners: ResMut<EventListeners>,
-    image_assets: Res<ImageAssets>,
-    mut query: Query<&mut TextureAtlasSprite, With<BlockComponent>>,
-    mut propagation_queue: ResMut<PropagationQueue>,
-    texture_to_block_map: Res<TextureToBlockMap>
+    settings: Res<Persistent<WorldSettings>>,
+    mut chunks: ResMut<Chunks>
 ) {
+    chunks.settings = *settings.get();
+
     commands.spawn(Camera2dBundle {
         transform: Transform::from_xyz(BOX_WIDTH * 5.0, BOX_WIDTH * 20.0, 0.),
         ..default()
@@ -762,27 +839,6 @@ fn init(
         },
         Cursor,
     ));
-
-    let mut calculations = 0;
-    for ((x, y), blk) in save_data.0.iter() {
-        if blk.texture_name != TextureName::PistonHead && blk.texture_name != TextureName::StickyPistonHead{
-            place(
-                &mut chunks,
-                *blk,
-                blk.orientation,
-                *x,
-                *y,
-                &mut listeners,
-                &mut commands,
-                &image_assets,
-                &mut query,
-                &mut propagation_queue,
-                &mut calculations,
-                &texture_to_block_map.0
-            );
-        }
-
-    }
 }
 
 #[derive(Component)]
@@ -818,8 +874,22 @@ pub fn update_selected_block(
         selected.0 = Some(REDSTONE_BLOCK)
     } else if keyboard_input.pressed(KeyCode::Backslash){
         selected.0 = Some(REDSTONE_LAMP)
+    } else if keyboard_input.pressed(KeyCode::BracketLeft){
+        selected.0 = Some(CONTAINER)
+    } else if keyboard_input.pressed(KeyCode::BracketRight){
+        selected.0 = block_registry().get("Obsidian")
+    } else if keyboard_input.pressed(KeyCode::Semicolon){
+        selected.0 = block_registry().get("HoneyBlock")
+    } else if keyboard_input.pressed(KeyCode::Apostrophe){
+        selected.0 = Some(WOODEN_BUTTON)
+    } else if keyboard_input.pressed(KeyCode::Comma){
+        selected.0 = Some(COPPER_BULB)
+    } else if keyboard_input.pressed(KeyCode::Period){
+        selected.0 = Some(SIGNAL_SOURCE)
+    } else if keyboard_input.pressed(KeyCode::Grave){
+        selected.0 = Some(CLOCK)
     } else if keyboard_input.pressed(KeyCode::Slash){
-        selected.0 = Some(GLASS)
+        selected.0 = block_registry().get("Glass")
     }
 }
 
@@ -885,8 +955,8 @@ fn update_cursor_position(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn mouse_input(
-    mut commands: Commands,
     mut listeners: ResMut<EventListeners>,
     buttons: Res<Input<MouseButton>>,
     mut selected_block: ResMut<SelectedBlock>,
@@ -894,12 +964,17 @@ pub fn mouse_input(
     mut chunks: ResMut<Chunks>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     q_camera: Query<(&Camera, &GlobalTransform)>,
-    image_assets: Res<ImageAssets>,
-    mut query: Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     mut propagation_queue: ResMut<PropagationQueue>,
     keyboard_input: Res<Input<KeyCode>>,
-    texture_to_block_map: Res<TextureToBlockMap>
+    texture_to_block_map: Res<TextureToBlockMap>,
+    mut panel: ResMut<SettingsPanel>,
+    ui_interactions: Query<&Interaction>
 ) {
+    // clicks on the settings panel are not meant for the world under it
+    if ui_interactions.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+
     let (camera, camera_transform) = q_camera.single();
     let (x, y, x_dist, y_dist) = if
         let Some(position) = q_windows
@@ -917,7 +992,7 @@ pub fn mouse_input(
 
     if buttons.just_pressed(MouseButton::Right) {
         if keyboard_input.pressed(KeyCode::ControlLeft) {
-            let blk = chunks.get_block_ref(x, y);
+            let blk = chunks.get_block(x, y);
             if let Some(blk) = blk {
                 selected_block.0 = Some(*texture_to_block_map.0.get(&blk.texture_name).unwrap());
             }
@@ -942,23 +1017,23 @@ pub fn mouse_input(
                         x,
                         y,
                         &mut listeners,
-                        &mut commands,
-                        &image_assets,
-                        &mut query,
                         &mut propagation_queue,
                         &mut calculations,
                         &texture_to_block_map.0
                     )
                 {
-                    interact(
-                        chunks.as_mut(),
-                        x,
-                        y,
-                        &mut commands,
-                        &image_assets,
-                        &mut query,
-                        &mut listeners
-                    );
+                    if chunks.get_block(x, y).is_some_and(|blk| !settings(&blk).is_empty()) {
+                        panel.target = Some((x, y));
+                    } else {
+                        interact(
+                            chunks.as_mut(),
+                            x,
+                            y,
+                            &mut listeners,
+                            &mut propagation_queue,
+                            &mut calculations
+                        );
+                    }
                 }
             }
         }
@@ -968,161 +1043,20 @@ pub fn mouse_input(
             x,
             y,
             &mut listeners,
-            &mut commands,
-            &image_assets,
-            &mut query,
             &mut propagation_queue,
             &mut calculations
         );
     }
 }
 
-fn get_mouse_coord(x: f32, y: f32) -> (i128, i128, f32, f32) {
+fn get_mouse_coord(x: f32, y: f32) -> (i64, i64, f32, f32) {
     let x_coord_raw = (CHUNK_SIZE.1 as f32) - (y + BOX_WIDTH / 2.0) / BOX_WIDTH;
     let y_coord_raw = (x + BOX_WIDTH / 2.0) / BOX_WIDTH;
 
     let x_dist = x_coord_raw - x_coord_raw.floor();
     let y_dist = y_coord_raw - y_coord_raw.floor();
 
-    (x_coord_raw.floor() as i128, y_coord_raw.floor() as i128, x_dist, y_dist)
-}
-
-fn get_connection(ports: &[bool; 4]) -> usize {
-    match *ports {
-        [true, true, true, true] => 10,
-        [false, true, true, true] => 9,
-        [true, false, true, true] => 8,
-        [true, true, false, true] => 7,
-        [true, true, true, false] => 6,
-        [false, false, true, true] => 5,
-        [false, true, false, true] => 4,
-        [false, true, true, false] => 3,
-        [true, false, false, true] => 2,
-        [true, false, true, false] => 1,
-        [true, true, false, false] => 0,
-        _ => 10,
-    }
-}
-
-fn get_state(blk: Block) -> usize {
-    match blk {
-        Block {
-            redstone: Some(Redstone { signal, kind: Some(RedstoneKind::Dust), output_ports, .. }),
-            ..
-        } => {
-            let conn_ind = get_connection(&output_ports);
-
-            conn_ind * 16 + (signal as usize)
-        }
-        Block{
-            redstone: Some(Redstone { signal, .. }),
-            texture_name: TextureName::RedstoneLamp,
-            ..
-        } => {
-            if signal > 0 {
-                1
-            } else{
-                0
-            }
-        }
-        Block {
-            redstone: Some(Redstone { signal, kind: Some(RedstoneKind::Mechanism), .. }),
-            mechanism: Some(MechanismKind::RedstoneTorch)
-            | Some(MechanismKind::Button)
-            | Some(MechanismKind::Lever),
-            ..
-        } => {
-            if signal > 0 { 1 } else { 0 }
-        }
-        Block { mechanism: Some(MechanismKind::Piston { extended, .. }), .. } => {
-            if !extended { 0 } else { 1 }
-        }
-        Block {
-            redstone: Some(Redstone { signal, .. }),
-            mechanism: Some(MechanismKind::Repeater { tick, .. }),
-            ..
-        } => {
-            let col_ind = if signal > 0 { 1 } else { 0 };
-            let row_ind = tick * 2;
-            (row_ind + col_ind) as usize
-        }
-        Block {
-            redstone: Some(Redstone { signal, .. }),
-            mechanism: Some(MechanismKind::Comparator { mode }),
-            ..
-        } => {
-            let col_ind = if signal > 0 { 1 } else { 0 };
-            let row_ind = if mode == ComparatorModes::Compare {
-                0
-            } else{
-                1
-            };
-            (row_ind * 2 + col_ind) as usize
-        }
-        Block {
-            redstone: Some(Redstone { signal, .. }),
-            mechanism: Some(MechanismKind::Observer),
-            ..
-        } => {
-            if signal > 0 { 1 } else { 0 }
-        }
-        _ => 0,
-    }
-}
-
-fn update_entity(
-    commands: &mut Commands,
-    chunks: &mut Chunks,
-    x: i128,
-    y: i128,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>
-) {
-    let curr_blk = chunks.get_block(x, y).clone();
-    let curr_entity = chunks.get_entity(x, y);
-
-    if let Some(blk) = curr_blk {
-        let Block { texture_name, orientation, .. } = blk;
-        let rotate = match orientation {
-            Orientation::Up => 0.0,
-            Orientation::Right => 3.0,
-            Orientation::Down => 2.0,
-            Orientation::Left => 1.0,
-        };
-        let state = get_state(blk);
-
-        if let Some(entity_handle) = *curr_entity {
-            if let Ok(mut sprite) = query.get_mut(entity_handle) {
-                sprite.index = state;
-            }
-        } else {
-            let handle = commands
-                .spawn((
-                    BlockComponent,
-                    SpriteSheetBundle {
-                        sprite: TextureAtlasSprite::new(state),
-                        texture_atlas: get_atlas(texture_name, image_assets),
-                        transform: Transform::from_xyz(
-                            (y as f32) * BOX_WIDTH,
-                            ((CHUNK_SIZE.0 - 1 - x) as f32) * BOX_WIDTH,
-                            0.0
-                        )
-                            .with_scale(Vec3 { x: 3.0, y: 3.0, z: 1.0 })
-                            .with_rotation(Quat::from_rotation_z((PI * rotate) / 2.0)),
-                        ..default()
-                    },
-                ))
-                .id();
-            *curr_entity = Some(handle);
-        }
-    } else {
-        if let Some(entity_handle) = curr_entity {
-            commands.entity(*entity_handle).despawn();
-        }
-
-        *curr_entity = None;
-        chunks.delete_chunk(x, y);
-    }
+    (x_coord_raw.floor() as i64, y_coord_raw.floor() as i64, x_dist, y_dist)
 }
 
 pub fn move_camera(
@@ -1162,206 +1096,109 @@ enum MyStates {
     InGame,
 }
 
-fn delayed_redstone_listeners(
+#[allow(clippy::too_many_arguments)]
+pub fn execute_listeners(
     mut listeners: ResMut<EventListeners>,
     mut chunks: ResMut<Chunks>,
-    mut propagation_queue: ResMut<PropagationQueue>
-) {
-    let mut calculations = 0;
-
-    propagation_queue.execute_queue(&mut chunks, &mut listeners, &mut calculations);
-
-    if !propagation_queue.is_empty() {
-        return;
-    }
-
-    let queue = listeners.repropagation_listener.clone();
-    listeners.repropagation_listener.clear();
-
-    for (x, y) in queue.iter() {
-        let prev_redstone = get_max_prev(&mut chunks, *x, *y);
-        let (from_port, previous_signal, prev_signal_type) = prev_redstone;
-        let transmitted_signal = if previous_signal > 0 { previous_signal - 1 } else { 0 };
-        propagate_signal_at(
-            &mut chunks,
-            *x,
-            *y,
-            from_port,
-            transmitted_signal,
-            previous_signal,
-            prev_signal_type,
-            &mut listeners,
-            &mut propagation_queue,
-            &mut calculations
-        );
-    }
-}
-
-fn execute_listeners(
-    mut listeners: ResMut<EventListeners>,
-    mut chunks: ResMut<Chunks>,
-    mut commands: Commands,
-    image_assets: Res<ImageAssets>,
-    mut query: Query<&mut TextureAtlasSprite, With<BlockComponent>>,
     mut propagation_queue: ResMut<PropagationQueue>,
     mut updates_timer: ResMut<UpdatesPerSecondTimer>,
+    mut tickets: ResMut<ChunkTickets>,
     texture_to_block_map: Res<TextureToBlockMap>
 ) {
-    if !propagation_queue.is_empty() {
-        return;
-    }
-
-    if listeners.repropagation_listener.len() > 0 {
-        return;
-    }
+    simulate_regions(
+        &mut chunks,
+        &mut listeners,
+        &mut propagation_queue,
+        &texture_to_block_map.0
+    );
+    tickets.tick(&listeners);
+    listeners.entity_map_update.clear();
+    updates_timer.number_of_updates += 1;
+}
 
-    let interactable_component_listener = listeners.interactable_component_listener.clone();
-    listeners.interactable_component_listener.clear();
-    let mut calculations = 0;
-    for ((x, y), on) in interactable_component_listener {
-        execute_mechanism(
-            &mut chunks,
-            x,
-            y,
-            on,
-            &mut listeners,
-            &mut commands,
-            &image_assets,
-            &mut query,
-            &mut propagation_queue,
-            &mut calculations,
-            &texture_to_block_map.0
-        );
-    }
+/// Runs one game tick: scheduled ticks that are due, in (game tick, priority) order, then the
+/// input changes queued during the last tick, in phase order. Input changes queued while this
+/// tick runs wait for the next one, so every component sees its inputs one tick late.
+pub fn simulate_tick(
+    chunks: &mut Chunks,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    texture_to_block_map: &HashMap<TextureName, Block>
+) {
+    let phases: Vec<Vec<_>> = TickPhase::iter()
+        .map(|phase| listeners.listener_mut(phase).drain().collect())
+        .collect();
 
-    let state_component_listener = listeners.state_component_listener.clone();
-    listeners.state_component_listener.clear();
     let mut calculations = 0;
-    for ((x, y), on) in state_component_listener {
+    while let Some((x, y, powered)) = listeners.scheduled_ticks.pop_due() {
         execute_mechanism(
-            &mut chunks,
+            chunks,
             x,
             y,
-            on,
-            &mut listeners,
-            &mut commands,
-            &image_assets,
-            &mut query,
-            &mut propagation_queue,
+            MechanismEvent::ScheduledTick(powered),
+            listeners,
+            propagation_queue,
             &mut calculations,
-            &texture_to_block_map.0
+            texture_to_block_map
         );
     }
 
-
-    let mechanism_listener = listeners.mechanism_listener.clone();
-    if mechanism_listener.len() > 0 {
-        // println!("{:?}", mechanism_listener);
-    };
-    listeners.mechanism_listener.clear();
-    let mut calculations = 0;
-    for ((x, y), on) in mechanism_listener {
-        execute_mechanism(
-            &mut chunks,
-            x,
-            y,
-            on,
-            &mut listeners,
-            &mut commands,
-            &image_assets,
-            &mut query,
-            &mut propagation_queue,
-            &mut calculations,
-            &texture_to_block_map.0
-        );
+    for events in phases {
+        let mut calculations = 0;
+        for ((x, y), event) in events {
+            execute_mechanism(
+                chunks,
+                x,
+                y,
+                event,
+                listeners,
+                propagation_queue,
+                &mut calculations,
+                texture_to_block_map
+            );
+        }
     }
 
-
-
-    let redstone_component_listener = listeners.redstone_component_listener.clone();
-    listeners.redstone_component_listener.clear();
-    if redstone_component_listener.len() > 0 {
-        // println!("{:?}", redstone_component_listener);
-    };
-    for ((x, y), on) in redstone_component_listener {
-        execute_mechanism(
-            &mut chunks,
-            x,
-            y,
-            on,
-            &mut listeners,
-            &mut commands,
-            &image_assets,
-            &mut query,
-            &mut propagation_queue,
-            &mut calculations,
-            &texture_to_block_map.0
-        );
+    // Entity updates only redraw cells; many of them (a dust's neighbours, an extending piston) did
+    // not change, and alerting them would pulse observers watching a block that stayed the same
+    for (x, y) in listeners.entity_map_update.iter() {
+        chunks.mark_dirty(*x, *y);
     }
-
-    let entity_map_update = listeners.entity_map_update.clone();
-    for (x, y) in entity_map_update {
-        update_entity(&mut commands, &mut chunks, x, y, &image_assets, &mut query);
-        alert_neighbours(x, y, &chunks, &mut listeners);
+    // Observers and other components watching a cell hear about every change made this tick,
+    // including blocks that were pushed in or out of it
+    for (x, y) in chunks.take_changes() {
+        alert_neighbours(x, y, chunks, listeners);
     }
-    listeners.entity_map_update.clear();
-    updates_timer.number_of_updates += 1;
+    listeners.scheduled_ticks.advance();
 }
 
 fn interact(
     chunks: &mut Chunks,
-    x: i128,
-    y: i128,
-    commands: &mut Commands,
-    image_assets: &ImageAssets,
-    query: &mut Query<&mut TextureAtlasSprite, With<BlockComponent>>,
-    listeners: &mut EventListeners
+    x: i64,
+    y: i64,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    calculations: &mut u32
 ) {
-    let blk = chunks.get_block(x, y);
-    match blk {
-        Some(Block { mechanism: Some(MechanismKind::Repeater { tick, .. }), .. }) => {
-            *tick = (*tick + 1) % 4;
-        }
-        Some(Block { mechanism: Some(MechanismKind::Button) | Some(MechanismKind::Lever), .. }) => {
-            listeners.turn_mechanism_on(x, y, &blk.unwrap());
-        }
-        Some(Block { mechanism: Some(MechanismKind::Comparator { mode }), .. }) => {
-            if *mode == ComparatorModes::Compare{
-                *mode = ComparatorModes::Subtract
-            } else{
-                *mode = ComparatorModes::Compare
-            }
-            listeners.turn_mechanism_on(x, y, &blk.unwrap());
-        }
-        _ => {}
+    if let Some(blk @ Block { mechanism: Some(kind), .. }) = chunks.get_block(x, y) {
+        get_mechanism(kind).on_interact(chunks, listeners, x, y, blk);
+    } else {
+        toggle_dust_shape(chunks, x, y, listeners, propagation_queue, calculations);
     }
-    update_entity(commands, chunks, x, y, image_assets, query);
-    alert_neighbours(x, y, &chunks, listeners);
+    chunks.mark_dirty(x, y);
+    alert_neighbours(x, y, chunks, listeners);
 }
 
 fn autosave(
     time: Res<Time>,
     mut autosave: ResMut<AutosaveTimer>,
-    mut save_data: ResMut<Persistent<SaveData>>,
-    chunks: Res<Chunks>
+    mut chunks: ResMut<Chunks>,
+    mut storage: ResMut<ChunkStorage>,
+    listeners: Res<EventListeners>
 ) {
     autosave.timer.tick(time.delta());
     if autosave.timer.finished() {
-        let mut current_state = Vec::new();
-        for ((chunk_x, chunk_y), chunk) in chunks.0.iter() {
-            for (u, row) in chunk.map.iter().enumerate() {
-                for (v, blk) in row.iter().enumerate() {
-                    let x = chunk_x * CHUNK_SIZE.0 + (u as i128);
-                    let y = chunk_y * CHUNK_SIZE.1 + (v as i128);
-                    if let Some(blk) = *blk {
-                        let tuple = ((x, y), blk);
-                        current_state.push(tuple);
-                    }
-                }
-            }
-        }
-        save_data.0 = current_state;
-        save_data.persist().ok();
+        storage.save_changed(&mut chunks, &listeners);
     }
 }
 
@@ -1412,9 +1249,9 @@ pub fn zoom_camera(
     }
 }
 
-pub fn alert_neighbours(x: i128, y: i128, chunks: &Chunks, listeners: &mut EventListeners) {
+pub fn alert_neighbours(x: i64, y: i64, chunks: &Chunks, listeners: &mut EventListeners) {
     for orientation in Orientation::iter() {
         let (next_x, next_y) = orientation.get_next_coord(x, y);
-        listeners.change_state(next_x, next_y, orientation.get_opposing(), &chunks);
+        listeners.change_state(next_x, next_y, orientation.get_opposing(), chunks);
     }
 }
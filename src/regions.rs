@@ -0,0 +1,342 @@
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+pub use super::*;
+
+/// Ticks with fewer cells of pending work than this are run sequentially, splitting them into
+/// regions costs more than it saves. Counted rather than timed so the same world always takes
+/// the same path.
+#[cfg(not(target_arch = "wasm32"))]
+pub const PARALLEL_MIN_SEEDS: usize = 64;
+
+/// Occupied cells at most this far apart (Manhattan distance) belong to the same region. Two
+/// circuits with a single empty cell between them both touch it, e.g. when a piston pushes a
+/// block into the gap.
+#[cfg(not(target_arch = "wasm32"))]
+const REGION_REACH: i64 = 2;
+
+/// Regions simulated together on one thread: a copy of the chunks they are in and the
+/// pending work inside them.
+#[cfg(not(target_arch = "wasm32"))]
+struct Batch {
+    /// The regions' cells and every cell next to them, which is all a tick can write to
+    owned: Vec<(i64, i64)>,
+    chunks: Chunks,
+    listeners: EventListeners,
+    propagation_queue: PropagationQueue,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Batch {
+    fn merge(self, chunks: &mut Chunks, listeners: &mut EventListeners) {
+        let Batch { owned, chunks: batch_chunks, listeners: batch_listeners, .. } = self;
+        for (x, y) in owned {
+            let blk = batch_chunks.get_block(x, y);
+            if chunks.get_block(x, y) != blk {
+                chunks.set_block(x, y, blk);
+                chunks.mark_dirty(x, y);
+            }
+        }
+
+        listeners.entity_map_update.extend(batch_listeners.entity_map_update);
+        listeners.mechanism_listener.extend(batch_listeners.mechanism_listener);
+        listeners.redstone_component_listener.extend(batch_listeners.redstone_component_listener);
+        listeners.interactable_component_listener.extend(
+            batch_listeners.interactable_component_listener
+        );
+        listeners.state_component_listener.extend(batch_listeners.state_component_listener);
+        let shift = listeners.scheduled_ticks.merge(batch_listeners.scheduled_ticks);
+        for (coord, mut moving) in batch_listeners.moving_blocks {
+            moving.shift_ticks(shift);
+            listeners.moving_blocks.insert(coord, moving);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find_root(parent: &mut [usize], mut id: usize) -> usize {
+    while parent[id] != id {
+        parent[id] = parent[parent[id]];
+        id = parent[id];
+    }
+    id
+}
+
+/// Every cell with pending work, sorted so regions are numbered the same way every run.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_seeds(listeners: &EventListeners) -> Vec<(i64, i64)> {
+    let mut seeds: Vec<(i64, i64)> = listeners.entity_map_update
+        .iter()
+        .chain(listeners.mechanism_listener.keys())
+        .chain(listeners.redstone_component_listener.keys())
+        .chain(listeners.interactable_component_listener.keys())
+        .chain(listeners.state_component_listener.keys())
+        .chain(listeners.scheduled_ticks.due_positions())
+        .copied()
+        .collect();
+    seeds.sort();
+    seeds.dedup();
+    seeds
+}
+
+/// Groups the seeds, and every occupied cell connected to them, into regions. Only chunks
+/// holding a seed are searched, so the cost follows the work pending rather than the size of
+/// the world. Regions reaching a cell outside those chunks are left out and reported by the
+/// returned flag, they run on the world itself. Each region is sorted and the regions are
+/// ordered by their first cell.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_regions(chunks: &Chunks, seeds: &[(i64, i64)]) -> (Vec<Vec<(i64, i64)>>, bool) {
+    let touched: HashSet<(i64, i64)> = seeds
+        .iter()
+        .map(|(x, y)| Chunks::from_world_coord(*x, *y).0)
+        .collect();
+    let mut region_of: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut parent: Vec<usize> = Vec::new();
+    let mut escapes: Vec<bool> = Vec::new();
+
+    for seed in seeds {
+        if region_of.contains_key(seed) {
+            continue;
+        }
+        let id = parent.len();
+        parent.push(id);
+        escapes.push(false);
+        region_of.insert(*seed, id);
+
+        let mut stack = vec![*seed];
+        while let Some((x, y)) = stack.pop() {
+            for dx in -REGION_REACH..=REGION_REACH {
+                let reach = REGION_REACH - dx.abs();
+                for dy in -reach..=reach {
+                    let next = (x + dx, y + dy);
+                    if !chunks.is_occupied(next.0, next.1) {
+                        continue;
+                    }
+                    if !touched.contains(&Chunks::from_world_coord(next.0, next.1).0) {
+                        escapes[id] = true;
+                    } else if let Some(other) = region_of.get(&next) {
+                        let other_root = find_root(&mut parent, *other);
+                        let root = find_root(&mut parent, id);
+                        if other_root != root {
+                            parent[other_root] = root;
+                            escapes[root] |= escapes[other_root];
+                        }
+                    } else {
+                        region_of.insert(next, id);
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<(i64, i64)>> = HashMap::new();
+    for (cell, id) in region_of {
+        let root = find_root(&mut parent, id);
+        groups.entry(root).or_default().push(cell);
+    }
+
+    let escaped = groups.keys().any(|root| escapes[*root]);
+    let mut regions: Vec<Vec<(i64, i64)>> = groups
+        .into_iter()
+        .filter(|(root, _)| !escapes[*root])
+        .map(|(_, mut cells)| {
+            cells.sort();
+            cells
+        })
+        .collect();
+    regions.sort();
+    (regions, escaped)
+}
+
+/// Deals the regions out to `count` batches of roughly equal size, keeping neighbouring
+/// regions together so they share chunk copies.
+#[cfg(not(target_arch = "wasm32"))]
+fn split_batches(
+    chunks: &Chunks,
+    listeners: &mut EventListeners,
+    regions: Vec<Vec<(i64, i64)>>,
+    count: usize
+) -> Vec<Batch> {
+    let total: usize = regions.iter().map(|cells| cells.len()).sum();
+    let batch_size = total.div_ceil(count);
+
+    let mut grouped: Vec<Vec<(i64, i64)>> = vec![Vec::new()];
+    for cells in regions {
+        if grouped.last().unwrap().len() >= batch_size {
+            grouped.push(Vec::new());
+        }
+        grouped.last_mut().unwrap().extend(cells);
+    }
+
+    let mut batch_of: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut batches = Vec::new();
+    for (id, cells) in grouped.into_iter().enumerate() {
+        let mut owned = HashSet::new();
+        for (x, y) in cells {
+            batch_of.insert((x, y), id);
+            owned.insert((x, y));
+            for orientation in Orientation::iter() {
+                owned.insert(orientation.get_next_coord(x, y));
+            }
+        }
+
+        let mut batch_chunks = chunks.empty_like();
+        for (x, y) in owned.iter() {
+            let (chunk_coord, _) = Chunks::from_world_coord(*x, *y);
+            if batch_chunks.loaded.contains_key(&chunk_coord) {
+                continue;
+            }
+            if let Some(chunk) = chunks.loaded.get(&chunk_coord) {
+                batch_chunks.loaded.insert(chunk_coord, chunk.clone());
+            }
+        }
+
+        let mut owned: Vec<(i64, i64)> = owned.into_iter().collect();
+        owned.sort();
+        batches.push(Batch {
+            owned,
+            chunks: batch_chunks,
+            listeners: EventListeners::new(),
+            propagation_queue: PropagationQueue::new(),
+        });
+    }
+
+    // listeners of regions left out stay with the world
+    listeners.mechanism_listener.retain(|coord, on| {
+        batch_of.get(coord).is_none_or(|id| {
+            batches[*id].listeners.mechanism_listener.insert(*coord, *on);
+            false
+        })
+    });
+    listeners.redstone_component_listener.retain(|coord, on| {
+        batch_of.get(coord).is_none_or(|id| {
+            batches[*id].listeners.redstone_component_listener.insert(*coord, *on);
+            false
+        })
+    });
+    listeners.interactable_component_listener.retain(|coord, on| {
+        batch_of.get(coord).is_none_or(|id| {
+            batches[*id].listeners.interactable_component_listener.insert(*coord, *on);
+            false
+        })
+    });
+    listeners.state_component_listener.retain(|coord, on| {
+        batch_of.get(coord).is_none_or(|id| {
+            batches[*id].listeners.state_component_listener.insert(*coord, *on);
+            false
+        })
+    });
+    listeners.entity_map_update.retain(|coord| {
+        batch_of.get(coord).is_none_or(|id| {
+            batches[*id].listeners.entity_map_update.insert(*coord);
+            false
+        })
+    });
+    for (id, batch) in batches.iter_mut().enumerate() {
+        batch.listeners.scheduled_ticks = listeners.scheduled_ticks.split_off(
+            |coord| batch_of.get(coord) == Some(&id)
+        );
+    }
+    // a moving block shares a region with the piston moving it, which is the only other cell
+    // that can land it
+    let moving: Vec<(i64, i64)> = listeners.moving_blocks
+        .keys()
+        .filter(|coord| batch_of.contains_key(*coord))
+        .copied()
+        .collect();
+    for coord in moving {
+        let moving_block = listeners.moving_blocks.remove(&coord).unwrap();
+        batches[batch_of[&coord]].listeners.moving_blocks.insert(coord, moving_block);
+    }
+
+    batches
+}
+
+/// Runs one tick. If the world's settings allow it and enough cells have pending work for the
+/// split to pay off, pending work is split into regions that cannot affect each other, which
+/// are simulated in parallel batches and written back in a fixed order so the result does not
+/// depend on scheduling. Regions reaching past the chunks with pending work run on the world
+/// itself alongside the batches. Chunks copied into a batch may hold cells of other regions,
+/// but a tick never reaches them and only the batch's own cells are written back.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn simulate_regions(
+    chunks: &mut Chunks,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    texture_to_block_map: &HashMap<TextureName, Block>
+) {
+    if !chunks.settings.parallel_regions {
+        simulate_tick(chunks, listeners, propagation_queue, texture_to_block_map);
+        return;
+    }
+    let seeds = get_seeds(listeners);
+    if seeds.len() >= PARALLEL_MIN_SEEDS {
+        simulate_seeded(chunks, listeners, propagation_queue, texture_to_block_map, &seeds);
+    } else {
+        simulate_tick(chunks, listeners, propagation_queue, texture_to_block_map);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn simulate_seeded(
+    chunks: &mut Chunks,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    texture_to_block_map: &HashMap<TextureName, Block>,
+    seeds: &[(i64, i64)]
+) {
+    let threads = rayon::current_num_threads();
+    let (regions, escaped) = if threads > 1 {
+        find_regions(chunks, seeds)
+    } else {
+        (Vec::new(), true)
+    };
+    if regions.len() + (escaped as usize) <= 1 {
+        simulate_tick(chunks, listeners, propagation_queue, texture_to_block_map);
+        return;
+    }
+
+    // Changes made between ticks are passed on once the batches are back
+    let pending = chunks.take_changes();
+    let mut batches = split_batches(chunks, listeners, regions, threads);
+    // The world's own tick only runs the regions left out of the batches. Regions are more
+    // than `REGION_REACH` apart, so the cells each tick can write to, a region and its
+    // neighbours, never overlap and no batch writes back a cell the world's tick changed.
+    // Each side works on its own chunks, listeners and scheduler; the palette is shared but
+    // copied on write.
+    rayon::join(
+        || {
+            batches.par_iter_mut().for_each(|batch| {
+                simulate_tick(
+                    &mut batch.chunks,
+                    &mut batch.listeners,
+                    &mut batch.propagation_queue,
+                    texture_to_block_map
+                );
+            })
+        },
+        || simulate_tick(chunks, listeners, propagation_queue, texture_to_block_map)
+    );
+
+    for batch in batches {
+        batch.merge(chunks, listeners);
+    }
+    // Writing the batches back records their changes again. Their neighbours were told
+    // already, telling them twice is harmless since listeners only note which sides changed.
+    let merged = chunks.take_changes();
+    for (x, y) in pending.into_iter().chain(merged) {
+        alert_neighbours(x, y, chunks, listeners);
+    }
+}
+
+/// Runs one tick. Wasm has no worker threads to spread regions over, so the whole world is
+/// simulated at once.
+#[cfg(target_arch = "wasm32")]
+pub fn simulate_regions(
+    chunks: &mut Chunks,
+    listeners: &mut EventListeners,
+    propagation_queue: &mut PropagationQueue,
+    texture_to_block_map: &HashMap<TextureName, Block>
+) {
+    simulate_tick(chunks, listeners, propagation_queue, texture_to_block_map);
+}
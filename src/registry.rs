@@ -0,0 +1,163 @@
+use std::sync::{ Mutex, OnceLock };
+pub use super::*;
+
+/// Blocks declared in `assets/blocks.json`, added on top of the built-in ones. Adding an entry
+/// and its image is enough to make a new decorative or conductive block placeable, and an
+/// entry naming a component added with `register_mechanism` runs it for every block placed.
+///
+/// The registry is not the whole block list. The built-in blocks, dust, slime and every
+/// built-in component among them, stay hard-coded in `lib.rs` and `texture.rs`: components
+/// keep their state in their own `MechanismKind` variants and the engine refers to these
+/// blocks and textures by name, so an entry cannot stand in for them.
+const BLOCK_REGISTRY_FILE: &str = include_str!("../assets/blocks.json");
+
+static BLOCK_REGISTRY: OnceLock<BlockRegistry> = OnceLock::new();
+
+/// Block lists added with `register_blocks`, read along with the built-in file
+static EXTRA_REGISTRY_FILES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+fn default_tile_size() -> u32 {
+    16
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockDef {
+    name: String,
+    /// Path of the sprite sheet, relative to the assets folder
+    texture: String,
+    #[serde(default = "default_tile_size")]
+    tile_size: u32,
+    columns: usize,
+    rows: usize,
+    sticky: Stickiness,
+    #[serde(default)]
+    push_reaction: PushReaction,
+    #[serde(default)]
+    conductivity: Conductivity,
+    symmetric: bool,
+    redstone: Option<Redstone>,
+    /// Name the block's component was registered under, which must be done before the block
+    /// is first used
+    #[serde(default)]
+    mechanism: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct BlockRegistry {
+    defs: Vec<BlockDef>,
+}
+
+/// The registered blocks, read once on first use. The file is embedded in the binary since
+/// the wasm build has no file system to read it from.
+pub fn block_registry() -> &'static BlockRegistry {
+    BLOCK_REGISTRY.get_or_init(|| {
+        let extra = EXTRA_REGISTRY_FILES.lock().unwrap();
+        BlockRegistry::parse(std::iter::once(BLOCK_REGISTRY_FILE).chain(extra.iter().copied()))
+    })
+}
+
+/// Adds the blocks of `source`, a list in the format of `assets/blocks.json`, to the registry,
+/// for blocks defined outside this crate. Must be called before the registry is first used.
+pub fn register_blocks(source: &'static str) {
+    let mut extra = EXTRA_REGISTRY_FILES.lock().unwrap();
+    if BLOCK_REGISTRY.get().is_some() {
+        panic!("blocks registered after the block registry was read");
+    }
+    extra.push(source);
+}
+
+impl BlockRegistry {
+    fn parse<'a>(sources: impl Iterator<Item = &'a str>) -> BlockRegistry {
+        let mut defs: Vec<BlockDef> = Vec::new();
+        for source in sources {
+            let source_defs: Vec<BlockDef> = serde_json
+                ::from_str(source)
+                .unwrap_or_else(|err| panic!("invalid block registry: {err}"));
+            defs.extend(source_defs);
+        }
+
+        for (idx, def) in defs.iter().enumerate() {
+            if TextureName::builtin_from_name(&def.name).is_some() {
+                panic!("registered block {} shadows a built-in block", def.name);
+            }
+            if defs[..idx].iter().any(|other| other.name == def.name) {
+                panic!("block {} is registered twice", def.name);
+            }
+        }
+
+        BlockRegistry { defs }
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    pub fn name(&self, id: u16) -> &str {
+        &self.defs[id as usize].name
+    }
+
+    pub fn id(&self, name: &str) -> Option<u16> {
+        self.defs
+            .iter()
+            .position(|def| def.name == name)
+            .map(|idx| idx as u16)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Block> {
+        self.id(name).map(|id| self.block(id))
+    }
+
+    pub fn block(&self, id: u16) -> Block {
+        let def = &self.defs[id as usize];
+        Block {
+            sticky: def.sticky,
+            push_reaction: def.push_reaction,
+            conductivity: def.conductivity,
+            orientation: Orientation::Up,
+            texture_name: TextureName::Registered(id),
+            symmetric: def.symmetric,
+            redstone: def.redstone,
+            mechanism: def.mechanism.as_ref().map(|name| {
+                registered_mechanism(name).unwrap_or_else(||
+                    panic!("block {} runs mechanism {}, which is not registered", def.name, name)
+                )
+            }),
+        }
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = Block> + '_ {
+        (0..self.defs.len()).map(|id| self.block(id as u16))
+    }
+}
+
+/// Sprite sheets of the registered blocks, indexed by registry id.
+#[derive(Resource)]
+pub struct RegistryAtlases(pub Vec<Handle<TextureAtlas>>);
+
+pub fn load_registry_atlases(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>
+) {
+    let handles = block_registry().defs
+        .iter()
+        .map(|def| {
+            let tile_size = def.tile_size as f32;
+            atlases.add(
+                TextureAtlas::from_grid(
+                    asset_server.load(def.texture.clone()),
+                    Vec2::new(tile_size, tile_size),
+                    def.columns,
+                    def.rows,
+                    None,
+                    None
+                )
+            )
+        })
+        .collect();
+    commands.insert_resource(RegistryAtlases(handles));
+}
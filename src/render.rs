@@ -0,0 +1,335 @@
+use bevy::render::render_resource::{ Extent3d, TextureDimension, TextureFormat };
+use bevy::render::texture::ImageSampler;
+pub use super::*;
+
+const TILE_SIZE: usize = 16;
+const CHUNK_WIDTH: usize = CHUNK_SIZE.1 as usize * TILE_SIZE;
+const CHUNK_HEIGHT: usize = CHUNK_SIZE.0 as usize * TILE_SIZE;
+
+/// Marker for the single sprite that draws a whole chunk
+#[derive(Component)]
+pub struct ChunkSprite;
+
+/// The sprite entity and composited texture of every chunk that has been drawn so far
+#[derive(Resource)]
+pub struct ChunkSprites(HashMap<(i64, i64), (Entity, Handle<Image>)>);
+
+impl Default for ChunkSprites {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkSprites {
+    pub fn new() -> ChunkSprites {
+        ChunkSprites(HashMap::new())
+    }
+}
+
+fn get_connection(ports: &[bool; 4]) -> usize {
+    match *ports {
+        [true, true, true, true] => 10,
+        [false, true, true, true] => 9,
+        [true, false, true, true] => 8,
+        [true, true, false, true] => 7,
+        [true, true, true, false] => 6,
+        [false, false, true, true] => 5,
+        [false, true, false, true] => 4,
+        [false, true, true, false] => 3,
+        [true, false, false, true] => 2,
+        [true, false, true, false] => 1,
+        [true, true, false, false] => 0,
+        [false, false, false, false] => 11,
+        _ => 10,
+    }
+}
+
+fn get_state(blk: Block) -> usize {
+    match blk {
+        Block {
+            redstone: Some(Redstone { signal, kind: Some(RedstoneKind::Dust), output_ports, .. }),
+            ..
+        } => {
+            let conn_ind = get_connection(&output_ports);
+
+            conn_ind * 16 + (signal as usize)
+        }
+        Block { mechanism: Some(kind), .. } => get_mechanism(kind).render_state(&blk),
+        _ => 0,
+    }
+}
+
+/// World-space bounds of a chunk, from the bottom left corner to the top right corner.
+fn chunk_bounds(chunk_x: i64, chunk_y: i64) -> Rect {
+    let first_x = chunk_x * CHUNK_SIZE.0;
+    let first_y = chunk_y * CHUNK_SIZE.1;
+    let left = (first_y as f32) * BOX_WIDTH - BOX_WIDTH / 2.0;
+    let top = ((CHUNK_SIZE.0 - 1 - first_x) as f32) * BOX_WIDTH + BOX_WIDTH / 2.0;
+    Rect::new(
+        left,
+        top - (CHUNK_SIZE.0 as f32) * BOX_WIDTH,
+        left + (CHUNK_SIZE.1 as f32) * BOX_WIDTH,
+        top
+    )
+}
+
+/// Copies the sprite of `blk` into its 16x16 cell of the chunk texture, rotated the same
+/// way the block is facing and scaled down from larger tiles. Returns false if the sprite
+/// sheet has not finished loading.
+fn draw_block(
+    pixels: &mut [u8],
+    u: usize,
+    v: usize,
+    blk: Block,
+    textures: &ChunkTextures
+) -> bool {
+    let ChunkTextures { image_assets, registry_atlases, atlases, images } = textures;
+    let atlas_handle = get_atlas(blk.texture_name, image_assets, registry_atlases);
+    let atlas = if let Some(atlas) = atlases.get(atlas_handle) {
+        atlas
+    } else {
+        return false;
+    };
+    let image = if let Some(image) = images.get(&atlas.texture) {
+        image
+    } else {
+        return false;
+    };
+    let rect = if let Some(rect) = atlas.textures.get(get_state(blk)) {
+        rect
+    } else {
+        return true;
+    };
+
+    let width = image.texture_descriptor.size.width as usize;
+    let height = image.texture_descriptor.size.height as usize;
+    if image.data.len() != width * height * 4 {
+        return true;
+    }
+    let tile_width = rect.width() as usize;
+    let tile_height = rect.height() as usize;
+
+    let turns = match blk.orientation {
+        Orientation::Up => 0,
+        Orientation::Right => 3,
+        Orientation::Down => 2,
+        Orientation::Left => 1,
+    };
+
+    for row in 0..TILE_SIZE {
+        for col in 0..TILE_SIZE {
+            // undo the counter clockwise quarter turns to find the source pixel
+            let (mut src_row, mut src_col) = (row, col);
+            for _ in 0..turns {
+                (src_row, src_col) = (src_col, TILE_SIZE - 1 - src_row);
+            }
+
+            let src_x = (rect.min.x as usize) + (src_col * tile_width) / TILE_SIZE;
+            let src_y = (rect.min.y as usize) + (src_row * tile_height) / TILE_SIZE;
+            if src_x >= width || src_y >= height {
+                continue;
+            }
+
+            let src = (src_y * width + src_x) * 4;
+            let dst = ((u * TILE_SIZE + row) * CHUNK_WIDTH + v * TILE_SIZE + col) * 4;
+            pixels[dst..dst + 4].copy_from_slice(&image.data[src..src + 4]);
+        }
+    }
+    true
+}
+
+/// Everything needed to look up block sprites
+struct ChunkTextures<'a> {
+    image_assets: &'a ImageAssets,
+    registry_atlases: &'a RegistryAtlases,
+    atlases: &'a Assets<TextureAtlas>,
+    images: &'a Assets<Image>,
+}
+
+/// Draws every block of the chunk. The flag is false if some sprite was still loading and the
+/// chunk has to be drawn again.
+fn draw_chunk(
+    chunk: &Chunk,
+    chunk_x: i64,
+    chunk_y: i64,
+    palette: &BlockPalette,
+    textures: &ChunkTextures
+) -> (Vec<u8>, bool) {
+    let mut pixels = vec![0; CHUNK_WIDTH * CHUNK_HEIGHT * 4];
+    let mut complete = true;
+    for ((x, y), blk) in chunk.blocks(chunk_x, chunk_y, palette) {
+        let u = (x - chunk_x * CHUNK_SIZE.0) as usize;
+        let v = (y - chunk_y * CHUNK_SIZE.1) as usize;
+        complete &= draw_block(&mut pixels, u, v, blk, textures);
+    }
+    (pixels, complete)
+}
+
+/// Draws each chunk as one sprite. A chunk's texture is only rebuilt after one of its cells
+/// changed, and chunks outside the camera's view are hidden and left dirty until they
+/// scroll back into view.
+#[allow(clippy::too_many_arguments)]
+pub fn render_chunks(
+    mut commands: Commands,
+    mut chunks: ResMut<Chunks>,
+    mut chunk_sprites: ResMut<ChunkSprites>,
+    mut images: ResMut<Assets<Image>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    image_assets: Res<ImageAssets>,
+    registry_atlases: Res<RegistryAtlases>,
+    q_camera: Query<(&OrthographicProjection, &GlobalTransform), With<Camera>>,
+    mut q_sprites: Query<&mut Visibility, With<ChunkSprite>>
+) {
+    let (projection, camera_transform) = if let Ok(camera) = q_camera.get_single() {
+        camera
+    } else {
+        return;
+    };
+    let camera_position = camera_transform.translation().truncate();
+    let view = Rect::from_corners(
+        projection.area.min + camera_position,
+        projection.area.max + camera_position
+    );
+
+    chunk_sprites.0.retain(|chunk_coord, (entity, handle)| {
+        if chunks.loaded.contains_key(chunk_coord) {
+            return true;
+        }
+        commands.entity(*entity).despawn();
+        images.remove(handle.id());
+        false
+    });
+
+    let Chunks { loaded, palette, .. } = &mut *chunks;
+    for ((chunk_x, chunk_y), chunk) in loaded.iter_mut() {
+        let bounds = chunk_bounds(*chunk_x, *chunk_y);
+        let visible = !bounds.intersect(view).is_empty();
+        let sprite = chunk_sprites.0.get(&(*chunk_x, *chunk_y));
+
+        if let Some((entity, _)) = sprite {
+            if let Ok(mut visibility) = q_sprites.get_mut(*entity) {
+                let target = if visible { Visibility::Inherited } else { Visibility::Hidden };
+                if *visibility != target {
+                    *visibility = target;
+                }
+            }
+        }
+
+        if !visible || !chunk.dirty {
+            continue;
+        }
+
+        let textures = ChunkTextures {
+            image_assets: &image_assets,
+            registry_atlases: &registry_atlases,
+            atlases: &atlases,
+            images: &images,
+        };
+        let (pixels, complete) = draw_chunk(chunk, *chunk_x, *chunk_y, palette, &textures);
+        chunk.dirty = !complete;
+
+        if let Some((_, handle)) = sprite {
+            if let Some(image) = images.get_mut(handle.id()) {
+                image.data = pixels;
+            }
+        } else {
+            let mut image = Image::new(
+                Extent3d {
+                    width: CHUNK_WIDTH as u32,
+                    height: CHUNK_HEIGHT as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                pixels,
+                TextureFormat::Rgba8UnormSrgb
+            );
+            image.sampler = ImageSampler::nearest();
+            let handle = images.add(image);
+
+            let scale = BOX_WIDTH / (TILE_SIZE as f32);
+            let entity = commands
+                .spawn((
+                    ChunkSprite,
+                    SpriteBundle {
+                        texture: handle.clone(),
+                        transform: Transform::from_translation(
+                            bounds.center().extend(0.0)
+                        ).with_scale(Vec3 { x: scale, y: scale, z: 1.0 }),
+                        ..default()
+                    },
+                ))
+                .id();
+            chunk_sprites.0.insert((*chunk_x, *chunk_y), (entity, handle));
+        }
+    }
+}
+
+/// Sprite drawing a block a piston is moving, identified by the cell it moves into and the
+/// game tick the move started on
+#[derive(Component)]
+pub struct MovingBlockSprite((i64, i64), u64);
+
+fn cell_center(x: i64, y: i64) -> Vec2 {
+    Vec2::new((y as f32) * BOX_WIDTH, ((CHUNK_SIZE.0 - x - 1) as f32) * BOX_WIDTH)
+}
+
+/// Draws the blocks pistons are moving as sprites sliding from the cell they left to the cell
+/// they move into, over the empty placeholder in the chunk texture.
+pub fn render_moving_blocks(
+    mut commands: Commands,
+    listeners: Res<EventListeners>,
+    time: Res<Time<Fixed>>,
+    image_assets: Res<ImageAssets>,
+    registry_atlases: Res<RegistryAtlases>,
+    mut q_sprites: Query<(Entity, &MovingBlockSprite, &mut Transform)>
+) {
+    let game_tick = listeners.scheduled_ticks.game_tick();
+    let progress_of = |moving: &MovingBlock| {
+        // the move starts showing once the tick that started it has run
+        let elapsed = (game_tick.saturating_sub(moving.start + 1) as f32) + time.overstep_percentage();
+        (elapsed / (PISTON_MOVE_TICKS as f32)).clamp(0.0, 1.0)
+    };
+    let position_of = |cell: (i64, i64), moving: &MovingBlock| {
+        let from = cell_center(moving.from.0, moving.from.1);
+        let to = cell_center(cell.0, cell.1);
+        from.lerp(to, progress_of(moving)).extend(1.0)
+    };
+
+    let mut drawn = HashSet::new();
+    for (entity, MovingBlockSprite(cell, start), mut transform) in q_sprites.iter_mut() {
+        match listeners.moving_blocks.get(cell) {
+            Some(moving) if moving.start == *start => {
+                transform.translation = position_of(*cell, moving);
+                drawn.insert(*cell);
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (cell, moving) in listeners.moving_blocks.iter() {
+        if drawn.contains(cell) {
+            continue;
+        }
+        let turns = match moving.blk.orientation {
+            Orientation::Up => 0.0,
+            Orientation::Right => 3.0,
+            Orientation::Down => 2.0,
+            Orientation::Left => 1.0,
+        };
+        commands.spawn((
+            MovingBlockSprite(*cell, moving.start),
+            SpriteSheetBundle {
+                texture_atlas: get_atlas(moving.blk.texture_name, &image_assets, &registry_atlases),
+                sprite: TextureAtlasSprite {
+                    index: get_state(moving.blk),
+                    custom_size: Some(Vec2::new(BOX_WIDTH, BOX_WIDTH)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position_of(*cell, moving)).with_rotation(
+                    Quat::from_rotation_z(turns * std::f32::consts::FRAC_PI_2)
+                ),
+                ..default()
+            },
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+use std::cmp::Reverse;
+use std::collections::{ BinaryHeap, VecDeque };
+pub use super::*;
+
+/// Order of ticks due on the same game tick, most urgent first, as in Minecraft's tile-tick
+/// list.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum TickPriority {
+    ExtremelyHigh,
+    VeryHigh,
+    High,
+    Normal,
+}
+
+/// Game ticks in a redstone tick. Component delays are counted in game ticks.
+pub const GAME_TICKS_PER_REDSTONE_TICK: u64 = 2;
+
+/// Game ticks between a signal reaching a component and `on_input_change` running: input
+/// changes are queued while the signal propagates and handled on the next tick. Ticks
+/// scheduled from `on_input_change` subtract it so the total delay matches vanilla.
+pub const INPUT_LATENCY: u64 = 1;
+
+/// Toggle histories untouched for this many game ticks are dropped. Longer than any window
+/// components count toggles over.
+const TOGGLE_CLEANUP_INTERVAL: u64 = 1200;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct ScheduledTick {
+    game_tick: u64,
+    priority: TickPriority,
+    /// Ticks with the same time and priority run in the order they were scheduled
+    order: u64,
+    x: i64,
+    y: i64,
+}
+
+/// The pending tick and toggle history of a component while pistons move it
+#[derive(Debug, Clone, Default)]
+pub struct DetachedTicks {
+    tick: Option<(ScheduledTick, bool)>,
+    toggles: Option<VecDeque<u64>>,
+}
+
+impl DetachedTicks {
+    /// Moves the pending tick and toggle history `by` game ticks later
+    pub fn postpone(&mut self, by: u64) {
+        if let Some((tick, _)) = &mut self.tick {
+            tick.game_tick += by;
+        }
+        for toggle in self.toggles.iter_mut().flatten() {
+            *toggle += by;
+        }
+    }
+}
+
+/// Renumbers the ticks a scheduler made by `split_off` handed out, as `merge` did, for ticks
+/// kept elsewhere meanwhile such as those of moving components.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderShift {
+    from: u64,
+    by: u64,
+}
+
+impl OrderShift {
+    fn apply(&self, tick: &mut ScheduledTick) {
+        if tick.order >= self.from {
+            tick.order += self.by;
+        }
+    }
+
+    pub fn apply_detached(&self, detached: &mut DetachedTicks) {
+        if let Some((tick, _)) = &mut detached.tick {
+            self.apply(tick);
+        }
+    }
+}
+
+/// Ticks components asked for, run in (game tick, priority) order. A cell has at most one
+/// pending tick, further requests are ignored until it has run.
+pub struct TickScheduler {
+    game_tick: u64,
+    next_order: u64,
+    /// The first order number handed out, past those of the scheduler it was split from
+    first_order: u64,
+    queue: BinaryHeap<Reverse<ScheduledTick>>,
+    /// The tick pending at each cell and whether the component's input is powered, kept up to
+    /// date by input changes that arrive while it waits
+    pending: HashMap<(i64, i64), (ScheduledTick, bool)>,
+    /// Game ticks at which components toggled, for those that stop working when toggled too
+    /// often
+    toggles: HashMap<(i64, i64), VecDeque<u64>>,
+}
+
+impl Default for TickScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickScheduler {
+    pub fn new() -> TickScheduler {
+        TickScheduler {
+            game_tick: 0,
+            next_order: 0,
+            first_order: 0,
+            queue: BinaryHeap::new(),
+            pending: HashMap::new(),
+            toggles: HashMap::new(),
+        }
+    }
+
+    pub fn game_tick(&self) -> u64 {
+        self.game_tick
+    }
+
+    pub fn advance(&mut self) {
+        self.game_tick += 1;
+        if self.game_tick.is_multiple_of(TOGGLE_CLEANUP_INTERVAL) {
+            let game_tick = self.game_tick;
+            self.toggles.retain(|_, toggles| {
+                toggles.back().is_some_and(|last| last + TOGGLE_CLEANUP_INTERVAL > game_tick)
+            });
+        }
+    }
+
+    pub fn is_scheduled(&self, x: i64, y: i64) -> bool {
+        self.pending.contains_key(&(x, y))
+    }
+
+    /// Runs the component at (x, y) `delay` game ticks from now, at least on the next tick.
+    pub fn schedule(&mut self, x: i64, y: i64, powered: bool, delay: u64, priority: TickPriority) {
+        if self.is_scheduled(x, y) {
+            return;
+        }
+
+        let tick = ScheduledTick {
+            game_tick: self.game_tick + delay.max(1),
+            priority,
+            order: self.next_order,
+            x,
+            y,
+        };
+        self.next_order += 1;
+        self.queue.push(Reverse(tick));
+        self.pending.insert((x, y), (tick, powered));
+    }
+
+    pub fn update_input(&mut self, x: i64, y: i64, powered: bool) {
+        if let Some((_, pending_powered)) = self.pending.get_mut(&(x, y)) {
+            *pending_powered = powered;
+        }
+    }
+
+    pub fn cancel(&mut self, x: i64, y: i64) {
+        self.pending.remove(&(x, y));
+    }
+
+    /// Removes the most urgent tick due by the current game tick, with whether the
+    /// component's input is powered.
+    pub fn pop_due(&mut self) -> Option<(i64, i64, bool)> {
+        while let Some(Reverse(tick)) = self.queue.peek().copied() {
+            if tick.game_tick > self.game_tick {
+                return None;
+            }
+            self.queue.pop();
+
+            // Entries of cancelled ticks stay in the queue until they come up
+            if let Some((pending, powered)) = self.pending.get(&(tick.x, tick.y)).copied() {
+                if pending == tick {
+                    self.pending.remove(&(tick.x, tick.y));
+                    return Some((tick.x, tick.y, powered));
+                }
+            }
+        }
+        None
+    }
+
+    /// Records that the component at (x, y) toggled now and returns how many times it toggled
+    /// in the last `window` game ticks, this one included.
+    pub fn record_toggle(&mut self, x: i64, y: i64, window: u64) -> usize {
+        let game_tick = self.game_tick;
+        self.toggles.entry((x, y)).or_default().push_back(game_tick);
+        self.recent_toggles(x, y, window)
+    }
+
+    /// How many times the component at (x, y) toggled in the last `window` game ticks
+    pub fn recent_toggles(&mut self, x: i64, y: i64, window: u64) -> usize {
+        let game_tick = self.game_tick;
+        if let Some(toggles) = self.toggles.get_mut(&(x, y)) {
+            while toggles.front().is_some_and(|toggle| toggle + window <= game_tick) {
+                toggles.pop_front();
+            }
+            toggles.len()
+        } else {
+            0
+        }
+    }
+
+    pub fn forget_toggles(&mut self, x: i64, y: i64) {
+        self.toggles.remove(&(x, y));
+    }
+
+    /// Takes the pending tick and toggle history of the component at (x, y) off its cell, for
+    /// components moved by pistons.
+    pub fn detach(&mut self, x: i64, y: i64) -> DetachedTicks {
+        DetachedTicks {
+            tick: self.pending.remove(&(x, y)),
+            toggles: self.toggles.remove(&(x, y)),
+        }
+    }
+
+    /// Gives a moved component back the ticks taken by `detach`, at its new cell. A tick that
+    /// came due while it moved runs on the next tick.
+    pub fn attach(&mut self, x: i64, y: i64, detached: DetachedTicks) {
+        if let Some((tick, powered)) = detached.tick {
+            let moved = ScheduledTick { x, y, ..tick };
+            self.queue.push(Reverse(moved));
+            self.pending.insert((x, y), (moved, powered));
+        }
+        if let Some(toggles) = detached.toggles {
+            self.toggles.insert((x, y), toggles);
+        }
+    }
+
+    /// Moves every pending tick and toggle history `by` game ticks later, for ticks that were
+    /// kept while their chunk was unloaded, so they resume with the delay they had left.
+    pub fn postpone(&mut self, by: u64) {
+        for (tick, _) in self.pending.values_mut() {
+            tick.game_tick += by;
+        }
+        for toggles in self.toggles.values_mut() {
+            for toggle in toggles.iter_mut() {
+                *toggle += by;
+            }
+        }
+        self.rebuild_queue();
+    }
+
+    /// Cells with a pending tick
+    pub fn positions(&self) -> impl Iterator<Item = &(i64, i64)> {
+        self.pending.keys()
+    }
+
+    /// Cells with a tick due by the current game tick
+    pub fn due_positions(&self) -> impl Iterator<Item = &(i64, i64)> {
+        self.pending
+            .iter()
+            .filter(|(_, (tick, _))| tick.game_tick <= self.game_tick)
+            .map(|(coord, _)| coord)
+    }
+
+    /// Moves the ticks of the cells matching `owned` into a scheduler of their own, at the same
+    /// game tick.
+    pub fn split_off(&mut self, owned: impl Fn(&(i64, i64)) -> bool) -> TickScheduler {
+        let mut split = TickScheduler {
+            game_tick: self.game_tick,
+            next_order: self.next_order,
+            first_order: self.next_order,
+            queue: BinaryHeap::new(),
+            pending: HashMap::new(),
+            toggles: HashMap::new(),
+        };
+        self.toggles.retain(|coord, toggles| {
+            if owned(coord) {
+                split.toggles.insert(*coord, std::mem::take(toggles));
+                false
+            } else {
+                true
+            }
+        });
+        self.pending.retain(|coord, entry| {
+            if owned(coord) {
+                split.pending.insert(*coord, *entry);
+                false
+            } else {
+                true
+            }
+        });
+        self.rebuild_queue();
+        split.rebuild_queue();
+        split
+    }
+
+    /// Takes back the ticks of a scheduler made by `split_off`. Splits number their ticks from
+    /// where this scheduler was, so the ticks they handed out are renumbered to follow the
+    /// ones handed out here meanwhile, and those of earlier merges. Ticks due together then
+    /// run in the order the splits were merged, whatever their position.
+    pub fn merge(&mut self, mut other: TickScheduler) -> OrderShift {
+        let shift = OrderShift {
+            from: other.first_order,
+            by: self.next_order - other.first_order,
+        };
+        for (tick, _) in other.pending.values_mut() {
+            shift.apply(tick);
+        }
+        self.next_order += other.next_order - other.first_order;
+        self.pending.extend(other.pending);
+        self.toggles.extend(other.toggles);
+        self.rebuild_queue();
+        shift
+    }
+
+    /// Refills the queue from the pending ticks, dropping entries of ticks that were cancelled
+    /// or moved to another scheduler
+    fn rebuild_queue(&mut self) {
+        self.queue = self.pending
+            .values()
+            .map(|(tick, _)| Reverse(*tick))
+            .collect();
+    }
+}
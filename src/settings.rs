@@ -0,0 +1,56 @@
+pub use super::*;
+
+/// Rules of the simulation the player can change, saved apart from the world. Settings missing
+/// from an older file take their default.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldSettings {
+    /// Torches toggled too often burn out, as in vanilla
+    pub torch_burnout: bool,
+    /// Pistons are also powered by sources diagonally or two blocks away, as in Java edition
+    pub quasi_connectivity: bool,
+    /// Most blocks a piston can move at once
+    pub push_limit: u32,
+    /// Busy ticks are split into regions simulated on several threads, with the same result
+    /// as the sequential tick. Switching it off gives a baseline to compare against.
+    pub parallel_regions: bool,
+}
+
+impl Default for WorldSettings {
+    fn default() -> WorldSettings {
+        WorldSettings {
+            torch_burnout: true,
+            quasi_connectivity: false,
+            push_limit: 12,
+            parallel_regions: true,
+        }
+    }
+}
+
+/// Highest push limit the keys go up to, well past vanilla's 12
+const MAX_PUSH_LIMIT: u32 = 64;
+
+/// F2 switches torch burnout, F3 quasi-connectivity and F4 parallel regions, F5 and F6 lower
+/// and raise the push limit. The simulation reads its settings from the chunks, so changes
+/// are copied there as well as saved.
+pub fn update_world_settings(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Persistent<WorldSettings>>,
+    mut chunks: ResMut<Chunks>
+) {
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        settings.torch_burnout = !settings.torch_burnout;
+    } else if keyboard_input.just_pressed(KeyCode::F3) {
+        settings.quasi_connectivity = !settings.quasi_connectivity;
+    } else if keyboard_input.just_pressed(KeyCode::F4) {
+        settings.parallel_regions = !settings.parallel_regions;
+    } else if keyboard_input.just_pressed(KeyCode::F5) {
+        settings.push_limit = settings.push_limit.saturating_sub(1).max(1);
+    } else if keyboard_input.just_pressed(KeyCode::F6) {
+        settings.push_limit = (settings.push_limit + 1).min(MAX_PUSH_LIMIT);
+    } else {
+        return;
+    }
+    settings.persist().ok();
+    chunks.settings = *settings.get();
+}
@@ -2,9 +2,12 @@ pub use super::*;
 
 use bevy_asset_loader::prelude::*;
 
+/// Sprite sheets of the built-in blocks. A new built-in block still needs a field here, a
+/// `TextureName` variant, a name in `BUILTIN_TEXTURES`, a case in `get_atlas` and an entry in
+/// `create_all_block_map`; only blocks in the block registry are added from data.
 #[derive(AssetCollection, Resource)]
 pub struct ImageAssets {
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 3, rows = 1))]
     #[asset(path = "images/redstone_torch.png")]
     #[asset(image(sampler = nearest))]
     redstone_torch: Handle<TextureAtlas>,
@@ -19,6 +22,11 @@ pub struct ImageAssets {
     #[asset(image(sampler = nearest))]
     button: Handle<TextureAtlas>,
 
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
+    #[asset(path = "images/wooden_button.png")]
+    #[asset(image(sampler = nearest))]
+    wooden_button: Handle<TextureAtlas>,
+
     #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
     #[asset(path = "images/lever.png")]
     #[asset(image(sampler = nearest))]
@@ -54,7 +62,12 @@ pub struct ImageAssets {
     #[asset(image(sampler = nearest))]
     sticky_piston_head: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 8, rows = 1))]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 16, rows = 1))]
+    #[asset(path = "images/container.png")]
+    #[asset(image(sampler = nearest))]
+    container: Handle<TextureAtlas>,
+
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 8, rows = 2))]
     #[asset(path = "images/redstone_repeater.png")]
     #[asset(image(sampler = nearest))]
     repeater: Handle<TextureAtlas>,
@@ -69,7 +82,7 @@ pub struct ImageAssets {
     #[asset(image(sampler = nearest))]
     target_block: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 176, rows = 1))]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 192, rows = 1))]
     #[asset(path = "images/redstone_dust_var.png")]
     #[asset(image(sampler = nearest))]
     redstone_dust_var: Handle<TextureAtlas>,
@@ -80,86 +93,36 @@ pub struct ImageAssets {
     slime_block: Handle<TextureAtlas>,
 
     #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/black_wool.png")]
-    #[asset(image(sampler = nearest))]
-    black_wool: Handle<TextureAtlas>,
-
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/blue_wool.png")]
-    #[asset(image(sampler = nearest))]
-    blue_wool: Handle<TextureAtlas>,
-
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/brown_wool.png")]
+    #[asset(path = "images/moving_block.png")]
     #[asset(image(sampler = nearest))]
-    brown_wool: Handle<TextureAtlas>,
+    moving_block: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/cyan_wool.png")]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 12, rows = 1))]
+    #[asset(path = "images/redstone_lamp.png")]
     #[asset(image(sampler = nearest))]
-    cyan_wool: Handle<TextureAtlas>,
+    redstone_lamp: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/gray_wool.png")]
-    #[asset(image(sampler = nearest))]
-    gray_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/green_wool.png")]
-    #[asset(image(sampler = nearest))]
-    green_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/light_blue_wool.png")]
-    #[asset(image(sampler = nearest))]
-    light_blue_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/light_gray_wool.png")]
-    #[asset(image(sampler = nearest))]
-    light_gray_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/lime_wool.png")]
-    #[asset(image(sampler = nearest))]
-    lime_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/magenta_wool.png")]
-    #[asset(image(sampler = nearest))]
-    magenta_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/orange_wool.png")]
-    #[asset(image(sampler = nearest))]
-    orange_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/pink_wool.png")]
-    #[asset(image(sampler = nearest))]
-    pink_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/purple_wool.png")]
-    #[asset(image(sampler = nearest))]
-    purple_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/red_wool.png")]
-    #[asset(image(sampler = nearest))]
-    red_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/white_wool.png")]
-    #[asset(image(sampler = nearest))]
-    white_wool: Handle<TextureAtlas>,
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/wool/yellow_wool.png")]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
+    #[asset(path = "images/copper_bulb.png")]
     #[asset(image(sampler = nearest))]
-    yellow_wool: Handle<TextureAtlas>,
+    copper_bulb: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 12, rows = 1))]
-    #[asset(path = "images/redstone_lamp.png")]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
+    #[asset(path = "images/signal_source.png")]
     #[asset(image(sampler = nearest))]
-    redstone_lamp: Handle<TextureAtlas>,
+    signal_source: Handle<TextureAtlas>,
 
-    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 1, rows = 1))]
-    #[asset(path = "images/glass.png")]
+    #[asset(texture_atlas(tile_size_x = 16.0, tile_size_y = 16.0, columns = 2, rows = 1))]
+    #[asset(path = "images/clock.png")]
     #[asset(image(sampler = nearest))]
-    glass: Handle<TextureAtlas>,
+    clock: Handle<TextureAtlas>,
 }
 
-pub fn get_atlas(texture_name: TextureName, image_assets: &ImageAssets) -> Handle<TextureAtlas> {
+pub fn get_atlas(
+    texture_name: TextureName,
+    image_assets: &ImageAssets,
+    registry_atlases: &RegistryAtlases
+) -> Handle<TextureAtlas> {
     match texture_name {
         TextureName::Dirt => image_assets.dirt.clone(),
         TextureName::RedstoneBlock => image_assets.redstone_block.clone(),
@@ -174,31 +137,20 @@ pub fn get_atlas(texture_name: TextureName, image_assets: &ImageAssets) -> Handl
         TextureName::Comparator => image_assets.comparator.clone(),
         TextureName::Observer => image_assets.observer.clone(),
         TextureName::SlimeBlock => image_assets.slime_block.clone(),
+        TextureName::MovingBlock => image_assets.moving_block.clone(),
         TextureName::Button => image_assets.button.clone(),
+        TextureName::WoodenButton => image_assets.wooden_button.clone(),
         TextureName::Lever => image_assets.lever.clone(),
-        TextureName::BlackWool => image_assets.black_wool.clone(),
-        TextureName::BlueWool => image_assets.blue_wool.clone(),
-        TextureName::BrownWool => image_assets.brown_wool.clone(),
-        TextureName::CyanWool => image_assets.cyan_wool.clone(),
-        TextureName::GrayWool => image_assets.gray_wool.clone(),
-        TextureName::GreenWool => image_assets.green_wool.clone(),
-        TextureName::LightBlueWool => image_assets.light_blue_wool.clone(),
-        TextureName::LightGrayWool => image_assets.light_gray_wool.clone(),
-        TextureName::LimeWool => image_assets.lime_wool.clone(),
-        TextureName::MagentaWool => image_assets.magenta_wool.clone(),
-        TextureName::OrangeWool => image_assets.orange_wool.clone(),
-        TextureName::PinkWool => image_assets.pink_wool.clone(),
-        TextureName::PurpleWool => image_assets.purple_wool.clone(),
-        TextureName::RedWool => image_assets.red_wool.clone(),
-        TextureName::WhiteWool => image_assets.white_wool.clone(),
-        TextureName::YellowWool => image_assets.yellow_wool.clone(),
         TextureName::RedstoneLamp => image_assets.redstone_lamp.clone(),
-        TextureName::Glass => image_assets.glass.clone()
-
+        TextureName::CopperBulb => image_assets.copper_bulb.clone(),
+        TextureName::SignalSource => image_assets.signal_source.clone(),
+        TextureName::Clock => image_assets.clock.clone(),
+        TextureName::Container => image_assets.container.clone(),
+        TextureName::Registered(id) => registry_atlases.0[id as usize].clone(),
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
 pub enum TextureName {
     Dirt,
     RedstoneTorch,
@@ -213,71 +165,84 @@ pub enum TextureName {
     Comparator,
     Observer,
     SlimeBlock,
+    /// The stone button, saved as `Button`
     Button,
+    WoodenButton,
     Lever,
-    BlackWool,
-    BlueWool,
-    BrownWool,
-    CyanWool,
-    GrayWool,
-    GreenWool,
-    LightBlueWool,
-    LightGrayWool,
-    LimeWool,
-    MagentaWool,
-    OrangeWool,
-    PinkWool,
-    PurpleWool,
-    RedWool,
-    WhiteWool,
-    YellowWool,
+    CopperBulb,
+    SignalSource,
+    Clock,
     RedstoneLamp,
-    Glass
+    Container,
+    /// Drawn empty, the block moving in is drawn sliding over it
+    MovingBlock,
+    /// A block declared in the block registry, by registry id
+    Registered(u16),
 }
 
+const BUILTIN_TEXTURES: [(TextureName, &str); 22] = [
+    (TextureName::Dirt, "Dirt"),
+    (TextureName::RedstoneTorch, "RedstoneTorch"),
+    (TextureName::RedstoneBlock, "RedstoneBlock"),
+    (TextureName::TargetBlock, "TargetBlock"),
+    (TextureName::RedstoneDust, "RedstoneDust"),
+    (TextureName::Piston, "Piston"),
+    (TextureName::StickyPiston, "StickyPiston"),
+    (TextureName::PistonHead, "PistonHead"),
+    (TextureName::StickyPistonHead, "StickyPistonHead"),
+    (TextureName::Repeater, "Repeater"),
+    (TextureName::Comparator, "Comparator"),
+    (TextureName::Observer, "Observer"),
+    (TextureName::SlimeBlock, "SlimeBlock"),
+    (TextureName::Button, "Button"),
+    (TextureName::Lever, "Lever"),
+    (TextureName::RedstoneLamp, "RedstoneLamp"),
+    (TextureName::Container, "Container"),
+    (TextureName::MovingBlock, "MovingBlock"),
+    (TextureName::WoodenButton, "WoodenButton"),
+    (TextureName::CopperBulb, "CopperBulb"),
+    (TextureName::SignalSource, "SignalSource"),
+    (TextureName::Clock, "Clock"),
+];
+
 impl TextureName {
+    /// The name blocks are saved under. Registered blocks use the name from the registry, so
+    /// saves stay valid when entries are added or reordered.
+    pub fn name(&self) -> &'static str {
+        if let TextureName::Registered(id) = self {
+            return block_registry().name(*id);
+        }
+        BUILTIN_TEXTURES
+            .iter()
+            .find(|(texture_name, _)| texture_name == self)
+            .map(|(_, name)| *name)
+            .unwrap()
+    }
+
+    pub fn builtin_from_name(name: &str) -> Option<TextureName> {
+        BUILTIN_TEXTURES
+            .iter()
+            .find(|(_, builtin_name)| *builtin_name == name)
+            .map(|(texture_name, _)| *texture_name)
+    }
+
+    pub fn from_name(name: &str) -> Option<TextureName> {
+        TextureName::builtin_from_name(name).or_else(||
+            block_registry().id(name).map(TextureName::Registered)
+        )
+    }
+
     pub fn get_string_value(&self) -> String {
-        let str = serde_json::to_string(&self).unwrap();
-        let string_val: String = serde_json::from_str(&str).unwrap();
-        string_val
+        self.name().to_string()
     }
 
     pub fn iter() -> Vec<TextureName> {
-        vec![
-            TextureName::Dirt,
-            TextureName::RedstoneTorch,
-            TextureName::RedstoneBlock,
-            TextureName::TargetBlock,
-            TextureName::RedstoneDust,
-            TextureName::Piston,
-            TextureName::StickyPiston,
-            TextureName::PistonHead,
-            TextureName::StickyPistonHead,
-            TextureName::Repeater,
-            TextureName::Comparator,
-            TextureName::Observer,
-            TextureName::SlimeBlock,
-            TextureName::Button,
-            TextureName::Lever,
-            TextureName::BlackWool,
-            TextureName::BlueWool,
-            TextureName::BrownWool,
-            TextureName::CyanWool,
-            TextureName::GrayWool,
-            TextureName::GreenWool,
-            TextureName::LightBlueWool,
-            TextureName::LightGrayWool,
-            TextureName::LimeWool,
-            TextureName::MagentaWool,
-            TextureName::OrangeWool,
-            TextureName::PinkWool,
-            TextureName::PurpleWool,
-            TextureName::RedWool,
-            TextureName::WhiteWool,
-            TextureName::YellowWool,
-            TextureName::RedstoneLamp,
-            TextureName::Glass
-        ]
+        let mut all_textures: Vec<TextureName> = BUILTIN_TEXTURES
+            .iter()
+            .map(|(texture_name, _)| *texture_name)
+            .collect();
+        all_textures.extend((0..block_registry().len()).map(|id| TextureName::Registered(id as u16)));
+        all_textures
     }
 
     pub fn texture_map() -> HashMap<String, TextureName>{
@@ -290,3 +255,18 @@ impl TextureName {
         hashmap
     }
 }
+
+impl Serialize for TextureName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for TextureName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        TextureName::from_name(&name).ok_or_else(||
+            serde::de::Error::custom(format!("unknown block {}", name))
+        )
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+/// Presses the button at (x, y), pressing it again after `repress_after` game ticks if given,
+/// and counts the game ticks the dust next to it stays powered
+fn pressed_ticks(harness: &mut Harness, x: i64, y: i64, repress_after: Option<u64>) -> u64 {
+    harness.interact(x, y);
+    let mut ticks = 0;
+    loop {
+        if Some(ticks) == repress_after {
+            harness.interact(x, y);
+        }
+        harness.tick(1);
+        if harness.signal(x, y + 1) == 0 {
+            return ticks;
+        }
+        ticks += 1;
+        assert!(ticks < 100, "button never released");
+    }
+}
+
+#[test]
+fn buttons_release_after_their_press_time() {
+    let mut harness = Harness::new();
+    harness.place(BUTTON, Orientation::Up, 0, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 0, 1);
+    harness.place(WOODEN_BUTTON, Orientation::Up, 5, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 5, 1);
+    harness.tick(4);
+
+    let stone = STONE_BUTTON_TICKS * GAME_TICKS_PER_REDSTONE_TICK;
+    let wooden = WOODEN_BUTTON_TICKS * GAME_TICKS_PER_REDSTONE_TICK;
+    assert_eq!((stone, wooden), (20, 30));
+    assert_eq!(pressed_ticks(&mut harness, 0, 0, None), stone);
+    assert_eq!(pressed_ticks(&mut harness, 5, 0, None), wooden);
+}
+
+#[test]
+fn pressing_a_pressed_button_does_not_extend_it() {
+    let mut harness = Harness::new();
+    harness.place(BUTTON, Orientation::Up, 0, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 0, 1);
+    harness.tick(4);
+    assert_eq!(pressed_ticks(&mut harness, 0, 0, Some(10)), 20);
+}
@@ -0,0 +1,79 @@
+use redstone_rust::*;
+
+#[test]
+fn busy_piston_stays_on_its_palette_entry_and_is_not_a_change() {
+    let mut chunks = Chunks::new();
+    chunks.set_block(3, 4, Some(PISTON));
+    chunks.take_changes();
+    let palette_len = chunks.palette.len();
+
+    set_movable(&mut chunks, 3, 4, false);
+    assert_eq!(chunks.get_block(3, 4).unwrap().push_reaction, PushReaction::Immovable);
+    assert_eq!(chunks.palette.len(), palette_len);
+    assert!(chunks.take_changes().is_empty());
+
+    set_movable(&mut chunks, 3, 4, true);
+    assert_eq!(chunks.get_block(3, 4), Some(PISTON));
+    assert!(chunks.take_changes().is_empty());
+}
+
+#[test]
+fn signal_and_component_state_share_a_palette_entry() {
+    let mut chunks = Chunks::new();
+    for signal in 0..16 {
+        let mut dust = REDSTONE_DUST;
+        dust.redstone.as_mut().unwrap().signal = signal;
+        chunks.set_block(0, signal as i64, Some(dust));
+    }
+    for tick in 0..4 {
+        let repeater = Block {
+            mechanism: Some(MechanismKind::Repeater { tick, locked: false, input: false }),
+            ..REPEATER
+        };
+        chunks.set_block(1, tick as i64, Some(repeater));
+        assert_eq!(chunks.get_block(1, tick as i64), Some(repeater));
+    }
+    assert_eq!(chunks.palette.len(), 2);
+    assert_eq!(chunks.take_changes().len(), 20);
+}
+
+/// A different definition of dust for every `n` up to 110592
+fn dust_variant(n: usize) -> Block {
+    let mut digits = n;
+    let mut digit = |base: usize| {
+        let value = digits % base;
+        digits /= base;
+        value
+    };
+    let mut dust = REDSTONE_DUST;
+    let redstone = dust.redstone.as_mut().unwrap();
+    let (inputs, outputs) = (digit(16), digit(16));
+    redstone.input_ports = [0, 1, 2, 3].map(|bit| inputs & (1 << bit) != 0);
+    redstone.output_ports = [0, 1, 2, 3].map(|bit| outputs & (1 << bit) != 0);
+    redstone.is_redstone_component = digit(2) == 1;
+    dust.orientation = [Orientation::Up, Orientation::Right, Orientation::Down, Orientation::Left][digit(4)];
+    dust.conductivity = [Conductivity::Conductive, Conductivity::Transparent, Conductivity::NonConductive][digit(3)];
+    dust.sticky = [Stickiness::None, Stickiness::Slime, Stickiness::Honey][digit(3)];
+    dust.texture_name = [TextureName::RedstoneDust, TextureName::Dirt, TextureName::RedstoneBlock][digit(3)];
+    dust.symmetric = digit(2) == 1;
+    dust
+}
+
+#[test]
+fn full_palette_leaves_the_cell_as_it_was() {
+    let mut chunks = Chunks::new();
+    let mut n = 0;
+    while chunks.palette.len() < u16::MAX as usize {
+        chunks.set_block(0, 0, Some(dust_variant(n)));
+        n += 1;
+    }
+    let last = chunks.get_block(0, 0);
+
+    chunks.set_block(0, 0, Some(dust_variant(n)));
+    assert_eq!(chunks.get_block(0, 0), last);
+    assert_eq!(chunks.palette.len(), u16::MAX as usize);
+
+    // blocks already in the palette can still be placed
+    chunks.set_block(1, 0, Some(dust_variant(0)));
+    assert_eq!(chunks.get_block(1, 0), Some(dust_variant(0)));
+}
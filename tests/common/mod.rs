@@ -0,0 +1,109 @@
+use bevy::utils::HashMap;
+use redstone_rust::*;
+
+/// A headless world ticked with `simulate_tick`, so every run takes the same sequential path.
+/// Shared by the integration tests and the benchmarks.
+pub struct Harness {
+    pub chunks: Chunks,
+    pub listeners: EventListeners,
+    pub propagation_queue: PropagationQueue,
+    pub texture_to_block_map: HashMap<TextureName, Block>,
+}
+
+#[allow(dead_code)]
+impl Harness {
+    pub fn new() -> Harness {
+        Harness {
+            chunks: Chunks::new(),
+            listeners: EventListeners::new(),
+            propagation_queue: PropagationQueue::new(),
+            texture_to_block_map: create_all_block_map(),
+        }
+    }
+
+    pub fn place(&mut self, blk: Block, orientation: Orientation, x: i64, y: i64) {
+        let mut calculations = 0;
+        place(
+            &mut self.chunks,
+            blk,
+            orientation,
+            x,
+            y,
+            &mut self.listeners,
+            &mut self.propagation_queue,
+            &mut calculations,
+            &self.texture_to_block_map
+        );
+    }
+
+    pub fn destroy(&mut self, x: i64, y: i64) {
+        let mut calculations = 0;
+        destroy(
+            &mut self.chunks,
+            x,
+            y,
+            &mut self.listeners,
+            &mut self.propagation_queue,
+            &mut calculations
+        );
+    }
+
+    /// Right clicks the component at (x, y), like flipping a lever or pressing a button
+    pub fn interact(&mut self, x: i64, y: i64) {
+        let blk = self.chunks.get_block(x, y).unwrap();
+        get_mechanism(blk.mechanism.unwrap()).on_interact(&mut self.chunks, &mut self.listeners, x, y, blk);
+    }
+
+    /// Runs `ticks` game ticks
+    pub fn tick(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            simulate_tick(
+                &mut self.chunks,
+                &mut self.listeners,
+                &mut self.propagation_queue,
+                &self.texture_to_block_map
+            );
+            self.listeners.entity_map_update.clear();
+        }
+    }
+
+    /// Runs `ticks` game ticks the way the game does, in parallel regions when the world's
+    /// settings allow it
+    pub fn tick_regions(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            simulate_regions(
+                &mut self.chunks,
+                &mut self.listeners,
+                &mut self.propagation_queue,
+                &self.texture_to_block_map
+            );
+            self.listeners.entity_map_update.clear();
+        }
+    }
+
+    /// Overwrites the signal held by the block at (x, y), without telling its neighbours
+    pub fn set_signal(&mut self, x: i64, y: i64, signal: u8) {
+        if let Some(mut blk @ Block { redstone: Some(redstone), .. }) = self.chunks.get_block(x, y) {
+            blk.redstone = Some(Redstone { signal, ..redstone });
+            self.chunks.set_block(x, y, Some(blk));
+        }
+    }
+
+    pub fn get_block(&self, x: i64, y: i64) -> Option<Block> {
+        self.chunks.get_block(x, y)
+    }
+
+    pub fn signal(&self, x: i64, y: i64) -> u8 {
+        match self.chunks.get_block(x, y) {
+            Some(Block { redstone: Some(Redstone { signal, .. }), .. }) => signal,
+            _ => 0,
+        }
+    }
+
+    /// Every block in the world with its position, sorted
+    pub fn blocks(&self) -> Vec<((i64, i64), Block)> {
+        let mut blocks: Vec<((i64, i64), Block)> = self.chunks.blocks().collect();
+        blocks.sort_by_key(|(coord, _)| *coord);
+        blocks
+    }
+}
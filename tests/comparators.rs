@@ -0,0 +1,79 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+/// Strength the comparator at (x, y) outputs; it holds one above it
+fn output(harness: &Harness, x: i64, y: i64) -> u8 {
+    harness.signal(x, y).saturating_sub(1)
+}
+
+/// A comparator at (5, 5) facing down, fed from the rear by dust at `rear` strength and from
+/// its left side by dust at `side` strength
+fn comparator(rear: u8, side: u8) -> Harness {
+    let mut harness = Harness::new();
+    harness.place(COMPARATOR, Orientation::Down, 5, 5);
+    let rear_len = 16 - rear as i64;
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 4 - rear_len, 5);
+    for x in 5 - rear_len..5 {
+        harness.place(REDSTONE_DUST, Orientation::Up, x, 5);
+    }
+    let side_len = 16 - side as i64;
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 5, 4 - side_len);
+    for y in 5 - side_len..5 {
+        harness.place(REDSTONE_DUST, Orientation::Up, 5, y);
+    }
+    harness.tick(10);
+    assert_eq!((harness.signal(4, 5), harness.signal(5, 4)), (rear, side));
+    harness
+}
+
+#[test]
+fn comparator_subtracts_or_compares_its_side_input() {
+    let mut harness = comparator(14, 10);
+    assert_eq!(output(&harness, 5, 5), 4);
+    harness.interact(5, 5);
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 14);
+
+    // a side stronger than the rear turns it off in both modes
+    let mut harness = comparator(10, 14);
+    assert_eq!(output(&harness, 5, 5), 0);
+    harness.interact(5, 5);
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 0);
+
+    // an equal side still lets the rear through when comparing
+    let mut harness = comparator(12, 12);
+    assert_eq!(output(&harness, 5, 5), 0);
+    harness.interact(5, 5);
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 12);
+}
+
+#[test]
+fn comparator_reads_a_container_through_a_solid_block() {
+    let mut harness = Harness::new();
+    harness.place(CONTAINER, Orientation::Up, 3, 5);
+    harness.place(DIRT, Orientation::Up, 4, 5);
+    harness.place(COMPARATOR, Orientation::Down, 5, 5);
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 0);
+
+    harness.interact(3, 5);
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 1);
+
+    // each click adds a stack of 64, 13 of the 27 it holds
+    let stacks = CONTAINER_CAPACITY / 64;
+    for _ in 1..13 {
+        harness.interact(3, 5);
+    }
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 7);
+    for _ in 13..stacks {
+        harness.interact(3, 5);
+    }
+    harness.tick(10);
+    assert_eq!(output(&harness, 5, 5), 15);
+}
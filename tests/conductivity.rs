@@ -0,0 +1,84 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn registered(name: &str) -> Block {
+    block_registry().get(name).unwrap()
+}
+
+/// A torch on `base` at (x, y) with `middle` above it and a second torch on top, going up
+fn torch_tower(harness: &mut Harness, x: i64, y: i64, base: Block, middle: Block) {
+    harness.place(base, Orientation::Up, x, y);
+    harness.place(REDSTONE_TORCH, Orientation::Up, x - 1, y);
+    harness.place(middle, Orientation::Up, x - 2, y);
+    harness.place(REDSTONE_TORCH, Orientation::Up, x - 3, y);
+}
+
+#[test]
+fn torch_tower_needs_a_conductive_block() {
+    let mut harness = Harness::new();
+    torch_tower(&mut harness, 10, 0, DIRT, DIRT);
+    torch_tower(&mut harness, 10, 5, DIRT, registered("Glass"));
+    harness.tick(20);
+    assert!(harness.signal(9, 0) > 0);
+    assert_eq!(harness.signal(7, 0), 0);
+    assert!(harness.signal(9, 5) > 0);
+    assert!(harness.signal(7, 5) > 0);
+}
+
+#[test]
+fn wool_relays_a_repeater_into_dust_and_glass_does_not() {
+    let mut harness = Harness::new();
+    for (y, relay) in [(0, registered("WhiteWool")), (5, registered("Glass"))] {
+        harness.place(REDSTONE_BLOCK, Orientation::Up, 11, y);
+        harness.place(REPEATER, Orientation::Up, 10, y);
+        harness.place(relay, Orientation::Up, 9, y);
+        harness.place(REDSTONE_DUST, Orientation::Up, 8, y);
+    }
+    harness.tick(20);
+    assert!(harness.signal(8, 0) > 0);
+    assert_eq!(harness.signal(8, 5), 0);
+}
+
+#[test]
+fn powered_lamp_lights_and_powers_dust_like_a_solid_block() {
+    let mut harness = Harness::new();
+    harness.place(DIRT, Orientation::Up, 10, 0);
+    harness.place(REDSTONE_TORCH, Orientation::Up, 9, 0);
+    harness.place(REDSTONE_LAMP, Orientation::Up, 8, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 7, 0);
+    harness.tick(20);
+    assert!(RedstoneLampMechanism::is_lit(&harness.get_block(8, 0).unwrap()));
+    assert!(harness.signal(7, 0) > 0);
+
+    harness.destroy(9, 0);
+    harness.tick(20);
+    assert!(!RedstoneLampMechanism::is_lit(&harness.get_block(8, 0).unwrap()));
+    assert_eq!(harness.signal(7, 0), 0);
+}
+
+#[test]
+fn unpowered_comparator_leaves_the_block_it_faces_off() {
+    let mut harness = Harness::new();
+    harness.place(COMPARATOR, Orientation::Up, 10, 0);
+    harness.place(DIRT, Orientation::Up, 9, 0);
+    harness.place(REDSTONE_LAMP, Orientation::Up, 8, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 9, 1);
+    harness.tick(20);
+    assert_eq!(harness.signal(9, 0), 0);
+    assert_eq!(harness.signal(9, 1), 0);
+    assert!(!RedstoneLampMechanism::is_lit(&harness.get_block(8, 0).unwrap()));
+}
+
+#[test]
+fn signal_source_powers_a_block_at_its_strength() {
+    let mut harness = Harness::new();
+    harness.place(SIGNAL_SOURCE, Orientation::Up, 10, 0);
+    harness.place(DIRT, Orientation::Up, 9, 0);
+    harness.place(REDSTONE_DUST, Orientation::Up, 8, 0);
+    configure(&mut harness.chunks, &mut harness.listeners, 10, 0, 0, 14);
+    harness.tick(20);
+    assert_eq!(harness.signal(9, 0), 14);
+    assert_eq!(harness.signal(8, 0), 14);
+}
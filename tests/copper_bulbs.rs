@@ -0,0 +1,45 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn is_lit(harness: &Harness, x: i64, y: i64) -> bool {
+    matches!(harness.get_block(x, y).unwrap().mechanism, Some(MechanismKind::CopperBulb { lit: true, .. }))
+}
+
+/// Strength the comparator at (x, y) outputs; it holds one above it
+fn comparator_output(harness: &Harness, x: i64, y: i64) -> u8 {
+    harness.signal(x, y).saturating_sub(1)
+}
+
+#[test]
+fn bulb_toggles_when_its_input_turns_on() {
+    let mut harness = Harness::new();
+    harness.place(LEVER, Orientation::Up, 4, 5);
+    harness.place(COPPER_BULB, Orientation::Up, 5, 5);
+    harness.place(COMPARATOR, Orientation::Down, 6, 5);
+    harness.tick(10);
+    assert!(!is_lit(&harness, 5, 5));
+    assert_eq!(comparator_output(&harness, 6, 5), 0);
+
+    harness.interact(4, 5);
+    harness.tick(10);
+    assert!(is_lit(&harness, 5, 5));
+    assert_eq!(comparator_output(&harness, 6, 5), 15);
+
+    // a held input, or a neighbour changing meanwhile, does not toggle it again
+    harness.tick(40);
+    harness.place(DIRT, Orientation::Up, 5, 6);
+    harness.tick(10);
+    assert!(is_lit(&harness, 5, 5));
+
+    // nor does the input turning off
+    harness.interact(4, 5);
+    harness.tick(10);
+    assert!(is_lit(&harness, 5, 5));
+
+    harness.interact(4, 5);
+    harness.tick(10);
+    assert!(!is_lit(&harness, 5, 5));
+    assert_eq!(comparator_output(&harness, 6, 5), 0);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn is_lit(harness: &Harness, x: i64, y: i64) -> bool {
+    RedstoneLampMechanism::is_lit(&harness.get_block(x, y).unwrap())
+}
+
+/// Right clicks the dust at (x, y)
+fn toggle(harness: &mut Harness, x: i64, y: i64) {
+    let mut calculations = 0;
+    assert!(toggle_dust_shape(
+        &mut harness.chunks,
+        x,
+        y,
+        &mut harness.listeners,
+        &mut harness.propagation_queue,
+        &mut calculations
+    ));
+}
+
+/// Dust at (5, 5) powered by a lever on its left, with the given dust next to it and lamps in
+/// the other cells around it. Returns which lamps lit.
+fn powered_shape(dust: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut harness = Harness::new();
+    harness.place(REDSTONE_DUST, Orientation::Up, 5, 5);
+    harness.place(LEVER, Orientation::Up, 5, 4);
+    let mut lamps = Vec::new();
+    for cell in [(4, 5), (6, 5), (5, 6)] {
+        if dust.contains(&cell) {
+            harness.place(REDSTONE_DUST, Orientation::Up, cell.0, cell.1);
+        } else {
+            harness.place(REDSTONE_LAMP, Orientation::Up, cell.0, cell.1);
+            lamps.push(cell);
+        }
+    }
+    harness.interact(5, 4);
+    harness.tick(10);
+    assert!(harness.signal(5, 5) > 0);
+    assert!(dust.iter().all(|(x, y)| harness.signal(*x, *y) > 0));
+    lamps.into_iter().filter(|(x, y)| is_lit(&harness, *x, *y)).collect()
+}
+
+#[test]
+fn line_powers_the_block_it_points_at() {
+    assert_eq!(powered_shape(&[]), vec![(5, 6)]);
+}
+
+#[test]
+fn corner_and_t_only_power_their_connections() {
+    assert_eq!(powered_shape(&[(4, 5)]), vec![]);
+    assert_eq!(powered_shape(&[(4, 5), (6, 5)]), vec![]);
+}
+
+#[test]
+fn toggling_unconnected_dust_switches_between_cross_and_dot() {
+    let mut harness = Harness::new();
+    // a powered block is not a connection, so the dust stays a cross
+    harness.place(LEVER, Orientation::Up, 3, 5);
+    harness.place(DIRT, Orientation::Up, 4, 5);
+    harness.place(REDSTONE_DUST, Orientation::Up, 5, 5);
+    let lamps = [(6, 5), (5, 4), (5, 6)];
+    for (x, y) in lamps {
+        harness.place(REDSTONE_LAMP, Orientation::Up, x, y);
+    }
+    harness.interact(3, 5);
+    harness.tick(10);
+    assert!(lamps.iter().all(|(x, y)| is_lit(&harness, *x, *y)));
+
+    toggle(&mut harness, 5, 5);
+    harness.tick(10);
+    assert!(harness.signal(5, 5) > 0);
+    assert!(lamps.iter().all(|(x, y)| !is_lit(&harness, *x, *y)));
+
+    toggle(&mut harness, 5, 5);
+    harness.tick(10);
+    assert!(lamps.iter().all(|(x, y)| is_lit(&harness, *x, *y)));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+/// Game ticks a flying machine takes to move one cell
+const STEP_TICKS: u64 = 13;
+
+/// A two-way flying machine, its observers at (x, y) and (x + 1, y + 3). A block placed left of
+/// the first observer sends it right, one placed right of the second sends it left, and it
+/// flies until something immovable stops its front.
+fn flying_machine(harness: &mut Harness, x: i64, y: i64) {
+    let parts = [
+        (0, 0, OBSERVER, Orientation::Right),
+        (0, 1, STICKY_PISTON, Orientation::Right),
+        (0, 2, SLIME, Orientation::Up),
+        (0, 3, SLIME, Orientation::Up),
+        (1, 0, SLIME, Orientation::Up),
+        (1, 1, SLIME, Orientation::Up),
+        (1, 2, STICKY_PISTON, Orientation::Left),
+        (1, 3, OBSERVER, Orientation::Left),
+    ];
+    for (dx, dy, blk, orientation) in parts {
+        harness.place(blk, orientation, x + dx, y + dy);
+    }
+    harness.tick(6);
+}
+
+/// Chunks holding a part of the machine
+fn machine_chunks(harness: &Harness) -> Vec<(i64, i64)> {
+    let mut chunks: Vec<(i64, i64)> = harness
+        .blocks()
+        .into_iter()
+        .filter(|(_, blk)| {
+            matches!(
+                blk.texture_name,
+                TextureName::Observer | TextureName::StickyPiston | TextureName::SlimeBlock
+            )
+        })
+        .map(|((x, y), _)| Chunks::from_world_coord(x, y).0)
+        .collect();
+    chunks.sort();
+    chunks.dedup();
+    chunks
+}
+
+#[test]
+fn flying_machine_crosses_chunks_both_ways() {
+    let mut harness = Harness::new();
+    // stops, one in each chunk
+    let obsidian = block_registry().get("Obsidian").unwrap();
+    harness.place(obsidian, Orientation::Up, 0, 21);
+    harness.place(obsidian, Orientation::Up, 1, 6);
+    flying_machine(&mut harness, 0, 10);
+    assert_eq!(machine_chunks(&harness), vec![(0, 0)]);
+
+    harness.place(DIRT, Orientation::Up, 0, 9);
+    harness.tick(10 * STEP_TICKS);
+    assert_eq!(machine_chunks(&harness), vec![(0, 1)]);
+    assert_eq!(harness.get_block(0, 20).map(|blk| blk.texture_name), Some(TextureName::SlimeBlock));
+
+    harness.place(DIRT, Orientation::Up, 1, 21);
+    harness.tick(20 * STEP_TICKS);
+    assert_eq!(machine_chunks(&harness), vec![(0, 0)]);
+    assert_eq!(harness.get_block(1, 7).map(|blk| blk.texture_name), Some(TextureName::SlimeBlock));
+}
+
@@ -0,0 +1,48 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn is_lit(harness: &Harness, x: i64, y: i64) -> bool {
+    RedstoneLampMechanism::is_lit(&harness.get_block(x, y).unwrap())
+}
+
+#[test]
+fn lamp_lights_when_a_power_source_is_pushed_next_to_it() {
+    let mut harness = Harness::new();
+    harness.place(REDSTONE_LAMP, Orientation::Up, 5, 5);
+    harness.place(PISTON, Orientation::Down, 3, 4);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 4, 4);
+    harness.place(LEVER, Orientation::Up, 2, 4);
+    harness.tick(10);
+    assert!(!is_lit(&harness, 5, 5));
+
+    harness.interact(2, 4);
+    harness.tick(10);
+    assert_eq!(harness.get_block(5, 4).map(|blk| blk.texture_name), Some(TextureName::RedstoneBlock));
+    assert!(is_lit(&harness, 5, 5));
+}
+
+#[test]
+fn lamp_lights_at_once_and_goes_out_two_redstone_ticks_after_losing_power() {
+    let mut harness = Harness::new();
+    // the dust shows when the lever's power arrives
+    harness.place(REDSTONE_DUST, Orientation::Up, 3, 5);
+    harness.place(LEVER, Orientation::Up, 4, 5);
+    harness.place(REDSTONE_LAMP, Orientation::Up, 5, 5);
+    harness.tick(10);
+
+    harness.interact(4, 5);
+    harness.tick(1);
+    assert!(harness.signal(3, 5) > 0);
+    harness.tick(INPUT_LATENCY);
+    assert!(is_lit(&harness, 5, 5));
+
+    harness.interact(4, 5);
+    harness.tick(1);
+    assert_eq!(harness.signal(3, 5), 0);
+    harness.tick(2 * GAME_TICKS_PER_REDSTONE_TICK - 1);
+    assert!(is_lit(&harness, 5, 5));
+    harness.tick(1);
+    assert!(!is_lit(&harness, 5, 5));
+}
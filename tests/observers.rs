@@ -0,0 +1,36 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+/// Counts the pulses the observer at (x, y) outputs over the next 10 game ticks
+fn pulses(harness: &mut Harness, x: i64, y: i64) -> usize {
+    let mut count = 0;
+    let mut was_on = false;
+    for _ in 0..10 {
+        harness.tick(1);
+        let on = harness.signal(x, y) > 0;
+        if on && !was_on {
+            count += 1;
+        }
+        was_on = on;
+    }
+    count
+}
+
+#[test]
+fn observer_sees_block_pushed_into_and_out_of_its_cell() {
+    let mut harness = Harness::new();
+    // watches (5, 4) and outputs into (5, 6)
+    harness.place(OBSERVER, Orientation::Right, 5, 5);
+    harness.place(STICKY_PISTON, Orientation::Down, 3, 4);
+    harness.place(DIRT, Orientation::Up, 4, 4);
+    harness.tick(10);
+    assert_eq!(harness.signal(5, 5), 0);
+
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    assert_eq!(pulses(&mut harness, 5, 5), 1);
+    harness.destroy(2, 4);
+    assert_eq!(pulses(&mut harness, 5, 5), 1);
+    assert_eq!(harness.get_block(4, 4).map(|blk| blk.texture_name), Some(TextureName::Dirt));
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn texture_at(harness: &Harness, x: i64, y: i64) -> Option<TextureName> {
+    harness.get_block(x, y).map(|blk| blk.texture_name)
+}
+
+/// A line of `len` dirt blocks starting at (x, y) and running down
+fn dirt_line(harness: &mut Harness, x: i64, y: i64, len: i64) {
+    for i in 0..len {
+        harness.place(DIRT, Orientation::Up, x + i, y);
+    }
+}
+
+#[test]
+fn blocked_piston_waits_for_a_neighbour_change() {
+    let mut harness = Harness::new();
+    let push_limit = harness.chunks.settings.push_limit as i64;
+    harness.place(PISTON, Orientation::Down, 3, 4);
+    dirt_line(&mut harness, 4, 4, push_limit + 1);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::Dirt));
+    assert!(!harness.listeners.scheduled_ticks.is_scheduled(3, 4));
+
+    // clearing the far end is not seen by the piston, a neighbour changing is
+    harness.destroy(4 + push_limit, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::Dirt));
+    harness.place(DIRT, Orientation::Up, 3, 5);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::PistonHead));
+    assert_eq!(texture_at(&harness, 4 + push_limit, 4), Some(TextureName::Dirt));
+}
+
+#[test]
+fn pushed_torch_breaks_and_repeater_is_carried() {
+    let mut harness = Harness::new();
+    for (y, blk) in [(4, REDSTONE_TORCH), (10, REPEATER)] {
+        harness.place(PISTON, Orientation::Down, 3, y);
+        harness.place(blk, Orientation::Down, 4, y);
+        harness.place(REDSTONE_BLOCK, Orientation::Up, 2, y);
+    }
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::PistonHead));
+    assert_eq!(texture_at(&harness, 5, 4), None);
+    assert_eq!(texture_at(&harness, 4, 10), Some(TextureName::PistonHead));
+    assert_eq!(texture_at(&harness, 5, 10), Some(TextureName::Repeater));
+}
+
+#[test]
+fn pushed_dust_breaks_and_dragged_dust_stays() {
+    let mut harness = Harness::new();
+    harness.place(PISTON, Orientation::Down, 3, 4);
+    harness.place(REDSTONE_DUST, Orientation::Up, 4, 4);
+    harness.place(DIRT, Orientation::Up, 5, 4);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    // the slime is pushed, the dust next to it is left where it is
+    harness.place(PISTON, Orientation::Down, 3, 10);
+    harness.place(SLIME, Orientation::Up, 4, 10);
+    harness.place(REDSTONE_DUST, Orientation::Up, 4, 11);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 10);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::PistonHead));
+    assert_eq!(texture_at(&harness, 5, 4), Some(TextureName::Dirt));
+    assert_eq!(texture_at(&harness, 6, 4), None);
+    assert_eq!(texture_at(&harness, 5, 10), Some(TextureName::SlimeBlock));
+    assert_eq!(texture_at(&harness, 4, 11), Some(TextureName::RedstoneDust));
+    assert_eq!(texture_at(&harness, 5, 11), None);
+}
+
+#[test]
+fn immovable_block_in_the_way_cancels_the_whole_push() {
+    let mut harness = Harness::new();
+    let obsidian = block_registry().get("Obsidian").unwrap();
+    // the slime drags the dirt next to it, whose way is blocked
+    harness.place(PISTON, Orientation::Down, 3, 4);
+    harness.place(SLIME, Orientation::Up, 4, 4);
+    harness.place(DIRT, Orientation::Up, 4, 5);
+    harness.place(obsidian, Orientation::Up, 5, 5);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::SlimeBlock));
+    assert_eq!(texture_at(&harness, 4, 5), Some(TextureName::Dirt));
+    assert!(get_power(&harness.chunks, 4, 4, Orientation::Down, 12).is_empty());
+
+    // an immovable block the slime would only drag is left behind
+    harness.destroy(4, 5);
+    harness.place(obsidian, Orientation::Up, 4, 5);
+    harness.place(DIRT, Orientation::Up, 3, 5);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 5, 4), Some(TextureName::SlimeBlock));
+    assert_eq!(harness.get_block(4, 5), Some(obsidian));
+}
+
+#[test]
+fn honey_drags_its_neighbours_but_not_slime() {
+    let mut harness = Harness::new();
+    let honey = block_registry().get("HoneyBlock").unwrap();
+    harness.place(PISTON, Orientation::Down, 3, 4);
+    harness.place(honey, Orientation::Up, 4, 4);
+    harness.place(DIRT, Orientation::Up, 4, 3);
+    harness.place(SLIME, Orientation::Up, 4, 5);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    harness.tick(10);
+    assert_eq!(harness.get_block(5, 4), Some(honey));
+    assert_eq!(texture_at(&harness, 5, 3), Some(TextureName::Dirt));
+    assert_eq!(texture_at(&harness, 4, 5), Some(TextureName::SlimeBlock));
+}
+
+#[test]
+fn pull_resistant_block_is_pushed_but_never_pulled_or_dragged() {
+    let mut harness = Harness::new();
+    let glazed = block_registry().get("GlazedTerracotta").unwrap();
+    let is_glazed = |harness: &Harness, x, y| texture_at(harness, x, y) == Some(glazed.texture_name);
+    harness.place(STICKY_PISTON, Orientation::Down, 3, 4);
+    harness.place(glazed, Orientation::Up, 4, 4);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    // the slime is pushed, the block next to it is not dragged along
+    harness.place(PISTON, Orientation::Down, 3, 10);
+    harness.place(SLIME, Orientation::Up, 4, 10);
+    harness.place(glazed, Orientation::Up, 4, 11);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 10);
+    harness.tick(10);
+    assert!(is_glazed(&harness, 5, 4));
+    assert_eq!(texture_at(&harness, 5, 10), Some(TextureName::SlimeBlock));
+    assert!(is_glazed(&harness, 4, 11));
+
+    // the sticky piston retracts and leaves it where it was pushed to
+    harness.destroy(2, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), None);
+    assert!(is_glazed(&harness, 5, 4));
+}
+
+#[test]
+fn retracting_sticky_piston_leaves_blocks_that_break_when_moved() {
+    let mut harness = Harness::new();
+    harness.place(STICKY_PISTON, Orientation::Down, 3, 4);
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), Some(TextureName::StickyPistonHead));
+    harness.place(BUTTON, Orientation::Down, 5, 4);
+
+    // a pushed button breaks, a pulled one is neither pulled nor broken
+    harness.destroy(2, 4);
+    harness.tick(10);
+    assert_eq!(texture_at(&harness, 4, 4), None);
+    assert_eq!(texture_at(&harness, 5, 4), Some(TextureName::Button));
+}
+
+fn is_extended(harness: &Harness, x: i64, y: i64) -> bool {
+    matches!(harness.get_block(x, y).unwrap().mechanism, Some(MechanismKind::Piston { extended: true, .. }))
+}
+
+/// A piston at (5, 5) pushing down with a lever at (3, 5), which powers the cell above the
+/// piston but not the piston itself
+fn piston_under_lever(quasi_connectivity: bool) -> Harness {
+    let mut harness = Harness::new();
+    harness.chunks.settings.quasi_connectivity = quasi_connectivity;
+    harness.place(PISTON, Orientation::Down, 5, 5);
+    harness.place(LEVER, Orientation::Up, 3, 5);
+    harness.tick(10);
+    harness
+}
+
+#[test]
+fn quasi_connected_piston_fires_from_the_block_above() {
+    let mut harness = piston_under_lever(true);
+    // the lever and a block next to the piston change together
+    harness.interact(3, 5);
+    harness.place(DIRT, Orientation::Up, 5, 6);
+    harness.tick(10);
+    assert!(is_extended(&harness, 5, 5));
+
+    harness.interact(3, 5);
+    harness.destroy(5, 6);
+    harness.tick(10);
+    assert!(!is_extended(&harness, 5, 5));
+}
+
+#[test]
+fn quasi_connected_piston_buds_until_a_neighbour_changes() {
+    let mut harness = piston_under_lever(true);
+    harness.interact(3, 5);
+    harness.tick(20);
+    assert!(!is_extended(&harness, 5, 5));
+
+    harness.place(DIRT, Orientation::Up, 5, 6);
+    harness.tick(10);
+    assert!(is_extended(&harness, 5, 5));
+
+    // and stays extended once the lever is off, until updated again
+    harness.interact(3, 5);
+    harness.tick(20);
+    assert!(is_extended(&harness, 5, 5));
+    harness.destroy(5, 6);
+    harness.tick(10);
+    assert!(!is_extended(&harness, 5, 5));
+}
+
+#[test]
+fn piston_ignores_the_block_above_without_quasi_connectivity() {
+    let mut harness = piston_under_lever(false);
+    harness.interact(3, 5);
+    harness.place(DIRT, Orientation::Up, 5, 6);
+    harness.tick(20);
+    assert!(!is_extended(&harness, 5, 5));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+/// Cells on the border of the `size` by `size` square whose top left corner is (x, y)
+fn ring(x: i64, y: i64, size: i64) -> Vec<(i64, i64)> {
+    let mut cells = Vec::new();
+    for i in 0..size {
+        for j in 0..size {
+            if i == 0 || j == 0 || i == size - 1 || j == size - 1 {
+                cells.push((x + i, y + j));
+            }
+        }
+    }
+    cells
+}
+
+#[test]
+fn dust_line_decays_one_level_per_block() {
+    let mut harness = Harness::new();
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 0, 0);
+    for y in 1..=17 {
+        harness.place(REDSTONE_DUST, Orientation::Up, 0, y);
+    }
+    harness.tick(1);
+    for y in 1..=17 {
+        assert_eq!(harness.signal(0, y), 16u8.saturating_sub(y as u8), "dust at {y}");
+    }
+
+    harness.destroy(0, 0);
+    harness.tick(1);
+    for y in 1..=17 {
+        assert_eq!(harness.signal(0, y), 0, "dust at {y}");
+    }
+}
+
+#[test]
+fn dust_grid_settles_to_the_distance_from_its_source() {
+    let mut harness = Harness::new();
+    for x in 0..6 {
+        for y in 0..6 {
+            harness.place(REDSTONE_DUST, Orientation::Up, x, y);
+        }
+    }
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 2, -1);
+    harness.tick(1);
+    for x in 0..6 {
+        for y in 0..6 {
+            let distance = (x - 2i64).abs() + y;
+            assert_eq!(harness.signal(x, y), 15 - distance as u8, "dust at {x}, {y}");
+        }
+    }
+
+    // a second source only raises the cells closer to it
+    harness.place(REDSTONE_BLOCK, Orientation::Up, 5, 6);
+    harness.tick(1);
+    for x in 0..6 {
+        for y in 0..6 {
+            let distance = ((x - 2i64).abs() + y).min((5 - x) + (5 - y));
+            assert_eq!(harness.signal(x, y), 15 - distance as u8, "dust at {x}, {y}");
+        }
+    }
+
+    harness.destroy(2, -1);
+    harness.destroy(5, 6);
+    harness.tick(1);
+    assert!(harness.blocks().iter().all(|((x, y), _)| harness.signal(*x, *y) == 0));
+}
+
+#[test]
+fn dust_loop_depowers_when_its_source_is_removed() {
+    let mut harness = Harness::new();
+    for (x, y) in ring(0, 0, 5) {
+        harness.place(REDSTONE_DUST, Orientation::Up, x, y);
+    }
+    harness.place(REDSTONE_BLOCK, Orientation::Up, -1, 0);
+    harness.tick(1);
+    assert_eq!(harness.signal(0, 0), 15);
+    // the far corner is reached both ways round, at the same distance
+    assert_eq!(harness.signal(4, 4), 7);
+    assert_eq!(harness.signal(4, 0), 11);
+
+    harness.destroy(-1, 0);
+    harness.tick(1);
+    for (x, y) in ring(0, 0, 5) {
+        assert_eq!(harness.signal(x, y), 0, "dust at {x}, {y}");
+    }
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn configure_at(harness: &mut Harness, x: i64, y: i64, idx: usize, value: u8) {
+    configure(&mut harness.chunks, &mut harness.listeners, x, y, idx, value);
+}
+
+/// A clock at (x, y) staying on and off for the given number of ticks
+fn clock(harness: &mut Harness, x: i64, y: i64, on_ticks: u8, off_ticks: u8) {
+    harness.place(CLOCK, Orientation::Up, x, y);
+    configure_at(harness, x, y, 0, on_ticks);
+    configure_at(harness, x, y, 1, off_ticks);
+}
+
+/// Copies of each world are built this far apart, a whole number of chunks, so that together
+/// they have enough pending work for ticks to be split into regions
+const COPY_SPACING: i64 = 64;
+const COPIES: i64 = 8;
+
+/// Builds the same world twice, `build` laying out a copy at each x offset, and runs it
+/// `ticks` ticks both sequentially and with parallel regions, checking after every tick that
+/// both worlds hold the same blocks. Returns the sequential world's blocks after each tick.
+fn run_both(build: impl Fn(&mut Harness, i64), ticks: u64) -> Vec<Vec<((i64, i64), Block)>> {
+    let mut sequential = Harness::new();
+    let mut parallel = Harness::new();
+    for copy in 0..COPIES {
+        build(&mut sequential, copy * COPY_SPACING);
+        build(&mut parallel, copy * COPY_SPACING);
+    }
+    assert!(parallel.chunks.settings.parallel_regions);
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    let mut history = Vec::new();
+    let mut split = 0;
+    for tick in 0..ticks {
+        if get_seeds(&parallel.listeners).len() >= PARALLEL_MIN_SEEDS {
+            split += 1;
+        }
+        sequential.tick(1);
+        pool.install(|| {
+            simulate_regions(
+                &mut parallel.chunks,
+                &mut parallel.listeners,
+                &mut parallel.propagation_queue,
+                &parallel.texture_to_block_map
+            );
+        });
+        parallel.listeners.entity_map_update.clear();
+        let blocks = sequential.blocks();
+        assert_eq!(parallel.blocks(), blocks, "tick {tick}");
+        history.push(blocks);
+    }
+    assert!(split > 0);
+    history
+}
+
+/// Observers facing each other, pulsing dust on both sides, in a grid spanning several chunks
+fn observer_clocks(harness: &mut Harness, offset: i64) {
+    for i in 0..40 {
+        let (x, y) = (offset + (i / 8) * 6, (i % 8) * 6);
+        harness.place(REDSTONE_DUST, Orientation::Up, x, y - 1);
+        harness.place(OBSERVER, Orientation::Left, x, y);
+        harness.place(OBSERVER, Orientation::Right, x, y + 1);
+        harness.place(REDSTONE_DUST, Orientation::Up, x, y + 2);
+    }
+}
+
+#[test]
+fn observer_clocks_match_a_sequential_run() {
+    let history = run_both(observer_clocks, 60);
+    let pulsed = history
+        .iter()
+        .filter(|blocks| blocks.iter().any(|(coord, blk)| {
+            *coord == (0, -1) && blk.redstone.is_some_and(|redstone| redstone.signal > 0)
+        }));
+    assert!(pulsed.count() > 0);
+}
+
+fn texture_in(blocks: &[((i64, i64), Block)], x: i64, y: i64) -> Option<TextureName> {
+    blocks
+        .iter()
+        .find(|(coord, _)| *coord == (x, y))
+        .map(|(_, blk)| blk.texture_name)
+}
+
+/// Rows of clocked pistons pushing dirt towards the next clock in the row. Extended, the
+/// dirt comes within `REGION_REACH` of that clock, so regions part and join as they run.
+fn piston_rows(harness: &mut Harness, offset: i64) {
+    for row in 0..4 {
+        for i in 0..8 {
+            let (x, y) = (offset + row * 3, i * 5);
+            let piston = if (row + i) % 2 == 0 { STICKY_PISTON } else { PISTON };
+            clock(harness, x, y, 4 + (i % 3) as u8, 5 + (row % 4) as u8);
+            harness.place(piston, Orientation::Right, x, y + 1);
+            harness.place(DIRT, Orientation::Up, x, y + 2);
+        }
+    }
+}
+
+#[test]
+fn pistons_moving_blocks_across_region_edges_match_a_sequential_run() {
+    let history = run_both(piston_rows, 80);
+    // the dirt of the first sticky piston is pushed out and pulled back
+    let pushed = history
+        .iter()
+        .position(|blocks| texture_in(blocks, 0, 3) == Some(TextureName::Dirt))
+        .unwrap();
+    assert!(history[pushed..].iter().any(|blocks| texture_in(blocks, 0, 2) == Some(TextureName::Dirt)));
+}
+
+/// Clocked lines of dust, each powering a piston at its far end, with the lines close enough
+/// that only the gap between them keeps them apart
+fn dust_lines(harness: &mut Harness, offset: i64) {
+    for row in 0..6 {
+        let x = offset + row * 3;
+        clock(harness, x, 0, 4 + row as u8, 6);
+        for y in 1..=12 {
+            harness.place(REDSTONE_DUST, Orientation::Up, x, y);
+        }
+        harness.place(STICKY_PISTON, Orientation::Right, x, 13);
+        harness.place(DIRT, Orientation::Up, x, 14);
+    }
+}
+
+#[test]
+fn dust_longer_than_the_region_reach_matches_a_sequential_run() {
+    let history = run_both(dust_lines, 80);
+    let powered = history.iter().filter(|blocks| texture_in(blocks, 0, 15) == Some(TextureName::Dirt));
+    assert!(powered.count() > 0);
+}
+
+/// Clocked sticky pistons straddling the borders between four chunks, with dust running from
+/// one chunk into the next and slime dragging blocks across
+fn chunk_border_circuits(harness: &mut Harness, offset: i64) {
+    let (width, height) = CHUNK_SIZE;
+    for (i, (x, y)) in [(width - 1, -2), (-1, height - 2), (width, height - 3), (-3, -1)]
+        .into_iter()
+        .enumerate()
+    {
+        let x = offset + x;
+        clock(harness, x, y, 4 + i as u8, 5);
+        for dy in 1..=4 {
+            harness.place(REDSTONE_DUST, Orientation::Up, x, y + dy);
+        }
+        harness.place(STICKY_PISTON, Orientation::Right, x, y + 5);
+        harness.place(SLIME, Orientation::Up, x, y + 6);
+        harness.place(DIRT, Orientation::Up, x + 1, y + 6);
+    }
+}
+
+#[test]
+fn regions_crossing_chunk_borders_match_a_sequential_run() {
+    let history = run_both(chunk_border_circuits, 80);
+    // the first clock's slime drags the dirt next to it along in the next chunk
+    let width = CHUNK_SIZE.0;
+    assert!(history.iter().any(|blocks| texture_in(blocks, width, 5) == Some(TextureName::Dirt)));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+use std::sync::Once;
+
+/// Counts the times its input turns on, two game ticks after each
+struct PulseCounter;
+
+impl Mechanism for PulseCounter {
+    fn phase(&self) -> TickPhase {
+        TickPhase::StateChange
+    }
+
+    fn on_input_change(&self, ctx: &mut MechanismContext, x: i64, y: i64, _blk: Block, powered: bool) {
+        if powered {
+            ctx.listeners.schedule_tick(x, y, true, 2, TickPriority::Normal);
+        }
+    }
+
+    fn on_scheduled_tick(&self, ctx: &mut MechanismContext, x: i64, y: i64, mut blk: Block, _powered: bool) {
+        if let Some(MechanismKind::Registered { id, state }) = blk.mechanism {
+            blk.mechanism = Some(MechanismKind::Registered { id, state: state + 1 });
+            ctx.chunks.set_block(x, y, Some(blk));
+        }
+    }
+}
+
+const PULSE_COUNTER_BLOCKS: &str = r#"[
+    {
+        "name": "PulseCounter",
+        "texture": "images/dirt.png",
+        "columns": 1,
+        "rows": 1,
+        "sticky": "None",
+        "symmetric": true,
+        "mechanism": "pulse_counter",
+        "redstone": {
+            "signal": 0,
+            "signal_type": null,
+            "is_redstone_component": true,
+            "kind": "Mechanism",
+            "signal_type_port_mapping": [null, null, null, null],
+            "input_ports": [true, true, true, true],
+            "output_ports": [false, false, false, false]
+        }
+    }
+]"#;
+
+/// Registers the counter and its block before any test reads the registry
+fn harness() -> Harness {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        register_mechanism("pulse_counter", &PulseCounter);
+        register_blocks(PULSE_COUNTER_BLOCKS);
+    });
+    Harness::new()
+}
+
+fn count(harness: &Harness, x: i64, y: i64) -> Option<u8> {
+    match harness.get_block(x, y)?.mechanism? {
+        MechanismKind::Registered { state, .. } => Some(state),
+        _ => None,
+    }
+}
+
+/// A counter at (0, 0) next to a lever
+fn counter_by_lever() -> Harness {
+    let mut harness = harness();
+    let counter = block_registry().get("PulseCounter").unwrap();
+    assert_eq!(counter.mechanism, registered_mechanism("pulse_counter"));
+    harness.place(counter, Orientation::Up, 0, 0);
+    harness.place(LEVER, Orientation::Up, 0, 1);
+    harness.tick(2);
+    harness
+}
+
+#[test]
+fn registered_component_runs_from_its_registry_entry() {
+    let mut harness = counter_by_lever();
+    assert_eq!(count(&harness, 0, 0), Some(0));
+
+    harness.interact(0, 1);
+    harness.tick(2);
+    assert_eq!(count(&harness, 0, 0), Some(0));
+    harness.tick(2);
+    assert_eq!(count(&harness, 0, 0), Some(1));
+
+    // turning the input off is not counted
+    harness.interact(0, 1);
+    harness.tick(4);
+    assert_eq!(count(&harness, 0, 0), Some(1));
+    harness.interact(0, 1);
+    harness.tick(4);
+    assert_eq!(count(&harness, 0, 0), Some(2));
+}
+
+#[test]
+fn registered_component_is_saved_by_name() {
+    let mut harness = counter_by_lever();
+    harness.interact(0, 1);
+    harness.tick(4);
+    assert_eq!(count(&harness, 0, 0), Some(1));
+    let counter = harness.get_block(0, 0).unwrap();
+    let saved = serde_json::to_string(&counter).unwrap();
+    assert!(saved.contains("\"pulse_counter\""), "{saved}");
+    assert_eq!(serde_json::from_str::<Block>(&saved).unwrap(), counter);
+
+    let dir = std::env::temp_dir().join(format!("redstone_rust_registered_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut storage = ChunkStorage::open(&dir);
+    storage.unload(&mut harness.chunks, &mut harness.listeners, 0, 0);
+    assert_eq!(harness.get_block(0, 0), None);
+    storage.load(
+        &mut harness.chunks,
+        0,
+        0,
+        &mut harness.listeners,
+        &mut harness.propagation_queue,
+        &harness.texture_to_block_map
+    );
+    assert_eq!(harness.get_block(0, 0), Some(counter));
+    let _ = std::fs::remove_dir_all(&dir);
+}
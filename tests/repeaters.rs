@@ -0,0 +1,62 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn is_locked(harness: &Harness, x: i64, y: i64) -> bool {
+    matches!(harness.get_block(x, y).unwrap().mechanism, Some(MechanismKind::Repeater { locked: true, .. }))
+}
+
+/// A repeater at (5, 5) fed by a lever at (4, 5) and outputting into dust at (6, 5), with a
+/// second repeater fed by a lever at (5, 7) pointing into its side
+fn locked_repeater() -> Harness {
+    let mut harness = Harness::new();
+    harness.place(LEVER, Orientation::Up, 4, 5);
+    harness.place(REPEATER, Orientation::Down, 5, 5);
+    harness.place(REDSTONE_DUST, Orientation::Up, 6, 5);
+    harness.place(REPEATER, Orientation::Left, 5, 6);
+    harness.place(LEVER, Orientation::Up, 5, 7);
+    harness.tick(10);
+    harness
+}
+
+#[test]
+fn side_repeater_freezes_the_output_until_it_turns_off() {
+    let mut harness = locked_repeater();
+    harness.interact(4, 5);
+    harness.tick(10);
+    assert!(harness.signal(6, 5) > 0);
+
+    // locked on: the input turning off is ignored
+    harness.interact(5, 7);
+    harness.tick(10);
+    assert!(is_locked(&harness, 5, 5));
+    harness.interact(4, 5);
+    harness.tick(10);
+    assert!(harness.signal(6, 5) > 0);
+
+    // unlocked, it catches up with its input
+    harness.interact(5, 7);
+    harness.tick(10);
+    assert!(!is_locked(&harness, 5, 5));
+    assert_eq!(harness.signal(6, 5), 0);
+}
+
+#[test]
+fn repeater_locked_off_ignores_its_input_turning_on() {
+    let mut harness = locked_repeater();
+    harness.interact(5, 7);
+    harness.tick(10);
+    assert!(is_locked(&harness, 5, 5));
+
+    // switched on and back off and on again while locked
+    for _ in 0..3 {
+        harness.interact(4, 5);
+        harness.tick(10);
+        assert_eq!(harness.signal(6, 5), 0);
+    }
+
+    harness.interact(5, 7);
+    harness.tick(10);
+    assert!(harness.signal(6, 5) > 0);
+}
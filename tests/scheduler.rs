@@ -0,0 +1,113 @@
+use redstone_rust::*;
+
+/// Every tick due now, in the order they run
+fn drain(scheduler: &mut TickScheduler) -> Vec<(i64, i64, bool)> {
+    std::iter::from_fn(|| scheduler.pop_due()).collect()
+}
+
+/// Advances `ticks` game ticks, returning the ticks that came due on each
+fn run(scheduler: &mut TickScheduler, ticks: u64) -> Vec<Vec<(i64, i64, bool)>> {
+    (0..ticks)
+        .map(|_| {
+            scheduler.advance();
+            drain(scheduler)
+        })
+        .collect()
+}
+
+#[test]
+fn ticks_run_by_game_tick_then_priority_then_order() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, false, 2, TickPriority::Normal);
+    scheduler.schedule(1, 0, false, 1, TickPriority::Normal);
+    scheduler.schedule(2, 0, false, 1, TickPriority::ExtremelyHigh);
+    scheduler.schedule(3, 0, false, 1, TickPriority::Normal);
+    scheduler.schedule(4, 0, false, 2, TickPriority::High);
+    scheduler.schedule(5, 0, false, 1, TickPriority::VeryHigh);
+    assert_eq!(run(&mut scheduler, 2), vec![
+        vec![(2, 0, false), (5, 0, false), (1, 0, false), (3, 0, false)],
+        vec![(4, 0, false), (0, 0, false)]
+    ]);
+}
+
+#[test]
+fn ticks_run_on_the_next_game_tick_at_the_earliest() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, true, 0, TickPriority::Normal);
+    assert!(drain(&mut scheduler).is_empty());
+    assert_eq!(run(&mut scheduler, 1), vec![vec![(0, 0, true)]]);
+}
+
+#[test]
+fn a_cell_has_one_pending_tick_following_its_input() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, false, 3, TickPriority::Normal);
+    scheduler.schedule(0, 0, false, 1, TickPriority::ExtremelyHigh);
+    scheduler.update_input(0, 0, true);
+    assert_eq!(run(&mut scheduler, 3), vec![vec![], vec![], vec![(0, 0, true)]]);
+    assert!(!scheduler.is_scheduled(0, 0));
+}
+
+#[test]
+fn cancelled_ticks_are_skipped_when_they_come_up() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, false, 1, TickPriority::Normal);
+    scheduler.cancel(0, 0);
+    assert!(!scheduler.is_scheduled(0, 0));
+    // rescheduled at the same time and later: only the new ticks run, once each
+    scheduler.schedule(0, 0, true, 1, TickPriority::Normal);
+    scheduler.schedule(1, 0, false, 1, TickPriority::Normal);
+    scheduler.cancel(1, 0);
+    scheduler.schedule(1, 0, true, 3, TickPriority::Normal);
+    assert_eq!(run(&mut scheduler, 3), vec![vec![(0, 0, true)], vec![], vec![(1, 0, true)]]);
+    assert!(drain(&mut scheduler).is_empty());
+}
+
+#[test]
+fn detached_ticks_follow_their_component() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, true, 2, TickPriority::Normal);
+    let mut detached = scheduler.detach(0, 0);
+    assert!(!scheduler.is_scheduled(0, 0));
+    detached.postpone(2);
+    scheduler.attach(5, 0, detached);
+    assert!(scheduler.is_scheduled(5, 0));
+    assert_eq!(run(&mut scheduler, 4), vec![vec![], vec![], vec![], vec![(5, 0, true)]]);
+
+    // a tick that came due while it moved runs straight away
+    scheduler.schedule(0, 0, false, 1, TickPriority::Normal);
+    let detached = scheduler.detach(0, 0);
+    assert_eq!(run(&mut scheduler, 2), vec![vec![], vec![]]);
+    scheduler.attach(1, 0, detached);
+    assert_eq!(drain(&mut scheduler), vec![(1, 0, false)]);
+}
+
+#[test]
+fn postponed_ticks_keep_the_delay_they_had_left() {
+    let mut scheduler = TickScheduler::new();
+    scheduler.schedule(0, 0, false, 1, TickPriority::Normal);
+    scheduler.schedule(1, 0, false, 2, TickPriority::Normal);
+    scheduler.postpone(3);
+    assert_eq!(run(&mut scheduler, 5), vec![vec![], vec![], vec![], vec![(0, 0, false)], vec![(1, 0, false)]]);
+}
+
+#[test]
+fn merged_splits_follow_the_ticks_scheduled_meanwhile() {
+    let mut scheduler = TickScheduler::new();
+    let mut first = scheduler.split_off(|_| false);
+    let mut second = scheduler.split_off(|_| false);
+    scheduler.schedule(9, 0, false, 1, TickPriority::Normal);
+    first.schedule(5, 0, false, 1, TickPriority::Normal);
+    first.schedule(7, 0, false, 1, TickPriority::Normal);
+    second.schedule(1, 0, false, 1, TickPriority::Normal);
+    scheduler.merge(first);
+    scheduler.merge(second);
+    scheduler.schedule(0, 0, false, 1, TickPriority::Normal);
+
+    scheduler.advance();
+    let order: Vec<(i64, i64)> = std::iter
+        ::from_fn(|| scheduler.pop_due())
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    assert_eq!(order, vec![(9, 0), (5, 0), (7, 0), (1, 0), (0, 0)]);
+}
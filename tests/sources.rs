@@ -0,0 +1,56 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn configure_at(harness: &mut Harness, x: i64, y: i64, idx: usize, value: u8) {
+    configure(&mut harness.chunks, &mut harness.listeners, x, y, idx, value);
+}
+
+/// Lengths of the runs of game ticks the block at (x, y) spends powered and unpowered over
+/// the next `ticks` game ticks, leaving out the first and last runs, which are cut off
+fn run_lengths(harness: &mut Harness, x: i64, y: i64, ticks: u64) -> Vec<(bool, u64)> {
+    let mut runs: Vec<(bool, u64)> = Vec::new();
+    for _ in 0..ticks {
+        harness.tick(1);
+        let on = harness.signal(x, y) > 0;
+        match runs.last_mut() {
+            Some((last, len)) if *last == on => *len += 1,
+            _ => runs.push((on, 1)),
+        }
+    }
+    runs.pop();
+    runs.remove(0);
+    runs
+}
+
+#[test]
+fn signal_source_powers_dust_at_its_strength() {
+    let mut harness = Harness::new();
+    harness.place(SIGNAL_SOURCE, Orientation::Up, 0, 0);
+    for y in 1..=16 {
+        harness.place(REDSTONE_DUST, Orientation::Up, 0, y);
+    }
+    for (strength, expected) in [(10, 10), (15, 15), (20, 15), (0, 0)] {
+        configure_at(&mut harness, 0, 0, 0, strength);
+        harness.tick(2);
+        for y in 1..=16 {
+            assert_eq!(harness.signal(0, y), (expected + 1u8).saturating_sub(y as u8), "dust at {y}");
+        }
+    }
+}
+
+#[test]
+fn clock_stays_on_and_off_for_its_periods() {
+    let mut harness = Harness::new();
+    harness.place(CLOCK, Orientation::Up, 0, 0);
+    configure_at(&mut harness, 0, 0, 0, 3);
+    configure_at(&mut harness, 0, 0, 1, 5);
+    let runs = run_lengths(&mut harness, 0, 0, 40);
+    assert!(runs.len() >= 6);
+    assert!(runs.iter().all(|run| *run == (true, 3) || *run == (false, 5)), "{runs:?}");
+
+    configure_at(&mut harness, 0, 0, 0, u8::MAX);
+    let clock = harness.get_block(0, 0).unwrap();
+    assert_eq!(settings(&clock)[0].value, CLOCK_MAX_TICKS);
+}
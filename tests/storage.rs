@@ -0,0 +1,82 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("redstone_rust_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn in_chunk(&(x, y): &(i64, i64)) -> bool {
+    x.div_euclid(CHUNK_SIZE.0) == 1 && y.div_euclid(CHUNK_SIZE.1) == 1
+}
+
+/// Counts the times dust at (x, y) turns on over `ticks` game ticks
+fn pulses(harness: &mut Harness, x: i64, y: i64, ticks: u64) -> usize {
+    let mut count = 0;
+    let mut was_on = harness.signal(x, y) > 0;
+    for _ in 0..ticks {
+        harness.tick(1);
+        let on = harness.signal(x, y) > 0;
+        if on && !was_on {
+            count += 1;
+        }
+        was_on = on;
+    }
+    count
+}
+
+#[test]
+fn unloaded_chunk_keeps_its_pending_work_until_loaded() {
+    let dir = temp_dir("unload");
+    let mut storage = ChunkStorage::open(dir.join("chunks"));
+    let mut harness = Harness::new();
+    // observers facing each other in chunk (1, 1) keep pulsing the dust on both sides
+    harness.place(REDSTONE_DUST, Orientation::Up, 20, 19);
+    harness.place(OBSERVER, Orientation::Left, 20, 20);
+    harness.place(OBSERVER, Orientation::Right, 20, 21);
+    harness.place(REDSTONE_DUST, Orientation::Up, 20, 22);
+    assert!(pulses(&mut harness, 20, 22, 20) > 0);
+
+    storage.unload(&mut harness.chunks, &mut harness.listeners, 1, 1);
+    assert!(storage.is_unloaded(1, 1));
+    assert!(harness.listeners.scheduled_ticks.positions().all(|coord| !in_chunk(coord)));
+    assert!(harness.listeners.moving_blocks.keys().all(|coord| !in_chunk(coord)));
+    for phase in [TickPhase::Interaction, TickPhase::StateChange] {
+        assert!(harness.listeners.listener_mut(phase).keys().all(|coord| !in_chunk(coord)));
+    }
+    harness.tick(50);
+    assert_eq!(harness.get_block(20, 20), None);
+
+    storage.load(
+        &mut harness.chunks,
+        1,
+        1,
+        &mut harness.listeners,
+        &mut harness.propagation_queue,
+        &harness.texture_to_block_map
+    );
+    assert_eq!(harness.get_block(20, 20).map(|blk| blk.texture_name), Some(TextureName::Observer));
+    assert!(pulses(&mut harness, 20, 22, 20) > 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn save_data_is_imported_once() {
+    let dir = temp_dir("import");
+    let blocks = vec![((3, 4), DIRT), ((40, -7), DIRT)];
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("save_data.json"), serde_json::to_string(&blocks).unwrap()).unwrap();
+
+    let mut storage = ChunkStorage::open(dir.join("chunks"));
+    assert!(storage.import(&dir));
+    assert!(!storage.is_new());
+    assert_eq!(storage.unloaded_len(), 2);
+    // the emptied file is not imported again
+    assert!(!storage.import(&dir));
+    assert!(!ChunkStorage::open(dir.join("chunks")).is_new());
+    let _ = std::fs::remove_dir_all(&dir);
+}
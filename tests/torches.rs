@@ -0,0 +1,65 @@
+mod common;
+
+use common::Harness;
+use redstone_rust::*;
+
+fn is_burnt_out(harness: &Harness, x: i64, y: i64) -> bool {
+    harness.get_block(x, y).unwrap().mechanism == Some(MechanismKind::BurntOutTorch)
+}
+
+/// A torch at (9, 0) standing on a clock that turns on and off every 2 game ticks, so the
+/// torch turns off every 4
+fn clocked_torch(harness: &mut Harness) {
+    let clock = Block { mechanism: Some(MechanismKind::Clock { on_ticks: 2, off_ticks: 2 }), ..CLOCK };
+    harness.place(clock, Orientation::Up, 10, 0);
+    harness.place(REDSTONE_TORCH, Orientation::Up, 9, 0);
+}
+
+/// Ticks until the torch at (x, y) burns out, at most `limit` game ticks, and returns how many
+/// game ticks that took and how many times the torch turned off meanwhile
+fn run_until_burnt_out(harness: &mut Harness, x: i64, y: i64, limit: u64) -> (u64, usize) {
+    let mut turned_off = 0;
+    let mut was_on = harness.signal(x, y) > 0;
+    for ticks in 1..=limit {
+        harness.tick(1);
+        let on = harness.signal(x, y) > 0;
+        if was_on && !on {
+            turned_off += 1;
+        }
+        was_on = on;
+        if is_burnt_out(harness, x, y) {
+            return (ticks, turned_off);
+        }
+    }
+    (limit, turned_off)
+}
+
+#[test]
+fn clocked_torch_burns_out_and_relights_160_game_ticks_later() {
+    let mut harness = Harness::new();
+    clocked_torch(&mut harness);
+    let (ticks, turned_off) = run_until_burnt_out(&mut harness, 9, 0, 100);
+    assert!(is_burnt_out(&harness, 9, 0));
+    assert!(ticks <= 60);
+    assert_eq!(turned_off, 8);
+
+    // the clock stopping does not relight it before it recovers
+    harness.destroy(10, 0);
+    harness.tick(159);
+    assert!(is_burnt_out(&harness, 9, 0));
+    assert_eq!(harness.signal(9, 0), 0);
+    harness.tick(1);
+    assert!(!is_burnt_out(&harness, 9, 0));
+    assert!(harness.signal(9, 0) > 0);
+}
+
+#[test]
+fn torch_does_not_burn_out_with_burnout_disabled() {
+    let mut harness = Harness::new();
+    harness.chunks.settings.torch_burnout = false;
+    clocked_torch(&mut harness);
+    let (ticks, turned_off) = run_until_burnt_out(&mut harness, 9, 0, 200);
+    assert!(!is_burnt_out(&harness, 9, 0));
+    assert_eq!(ticks, 200);
+    assert!(turned_off >= 45, "turned off {turned_off} times");
+}